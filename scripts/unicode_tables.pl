@@ -0,0 +1,69 @@
+#!/usr/bin/perl
+# Regenerates src/unicode_tables.rs from the Unicode data shipped with perl:
+#
+#   perl scripts/unicode_tables.pl > src/unicode_tables.rs
+use strict;
+use warnings;
+use Unicode::UCD qw(prop_aliases prop_values prop_value_aliases prop_invlist);
+
+my @binary = qw(
+  Alphabetic Lowercase Uppercase White_Space
+  ID_Start ID_Continue XID_Start XID_Continue
+);
+
+sub uniq {
+  my %seen;
+  grep { !$seen{$_}++ } @_;
+}
+
+sub ranges {
+  my @list = prop_invlist($_[0]);
+  push @list, 0x110000 if @list % 2;
+  my @ranges;
+  while (my ($from, $to) = splice @list, 0, 2) {
+    $to -= 1;
+    # Surrogates are not code points that UTF-8 can encode.
+    if ($from < 0xD800 && $to >= 0xD800) {
+      push @ranges, [$from, 0xD7FF];
+      $from = 0xE000;
+    }
+    $from = 0xE000 if $from >= 0xD800 && $from <= 0xDFFF;
+    $to = 0xD7FF if $to >= 0xD800 && $to <= 0xDFFF;
+    push @ranges, [$from, $to] if $from <= $to;
+  }
+  @ranges;
+}
+
+my (@categories, @scripts, @properties);
+for my $value (sort { $a cmp $b } prop_values('gc')) {
+  push @categories, [[uniq prop_value_aliases('gc', $value)], "gc=$value"];
+}
+for my $value (sort { $a cmp $b } prop_values('sc')) {
+  push @scripts, [[uniq prop_value_aliases('sc', $value)], "sc=$value"];
+}
+for my $name (@binary) {
+  push @properties, [[uniq prop_aliases($name)], $name];
+}
+
+sub table {
+  my ($name, @table) = @_;
+  print "\npub(crate) static $name: &[Property] = &[\n";
+  for my $property (@table) {
+    my ($names, $query) = @$property;
+    my @ranges = ranges($query);
+    print "  (&[", join(", ", map { "\"$_\"" } @$names), "], &[\n";
+    while (my @line = splice @ranges, 0, 4) {
+      print "    ", join(" ", map { sprintf "('\\u{%X}', '\\u{%X}'),", @$_ } @line), "\n";
+    }
+    print "  ]),\n";
+  }
+  print "];\n";
+}
+
+printf "// Generated by scripts/unicode_tables.pl from Unicode %s; do not edit.\n",
+  Unicode::UCD::UnicodeVersion();
+print "#![cfg_attr(rustfmt, rustfmt::skip)]\n\n";
+print "pub(crate) type Property = (&'static [&'static str], &'static [(char, char)]);\n";
+table('GENERAL_CATEGORIES', @categories);
+table('SCRIPTS', @scripts);
+table('PROPERTIES', @properties);
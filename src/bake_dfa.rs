@@ -1,26 +1,48 @@
 use crate::*;
 
-#[derive(Debug, Clone)]
-pub struct BakedDfa<X>(Vec<(bool, BTreeMap<X, usize>)>);
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BakedDfa<X>(pub Vec<(bool, BTreeMap<X, usize>)>);
 
 pub fn bake_dfa<'a, X: Clone + Ord + 'a, D: Dfa<X>>(
   dfa: D,
   alphabet: impl Clone + IntoIterator<Item = &'a X>,
 ) -> BakedDfa<X>
+where
+  D::State: Clone + Ord,
+{
+  bake_tagged(dfa, alphabet, |_| ()).0
+}
+
+pub fn bake_tagged<'a, X: Clone + Ord + 'a, D: Dfa<X>, T>(
+  dfa: D,
+  alphabet: impl Clone + IntoIterator<Item = &'a X>,
+  tag: impl Fn(&D::State) -> T,
+) -> (BakedDfa<X>, Vec<T>)
 where
   D::State: Clone + Ord,
 {
   let mut states = Vec::new();
+  let mut tags = Vec::new();
   let mut reverse = BTreeMap::new();
 
-  visit(&dfa, alphabet, &mut states, &mut reverse, &dfa.initial());
+  visit(
+    &dfa,
+    alphabet,
+    &tag,
+    &mut states,
+    &mut tags,
+    &mut reverse,
+    &dfa.initial(),
+  );
 
-  return BakedDfa(states);
+  return (BakedDfa(states), tags);
 
-  fn visit<'a, X: Clone + Ord + 'a, D: Dfa<X>>(
+  fn visit<'a, X: Clone + Ord + 'a, D: Dfa<X>, T>(
     dfa: &D,
     alphabet: impl Clone + IntoIterator<Item = &'a X>,
+    tag: &impl Fn(&D::State) -> T,
     states: &mut Vec<(bool, BTreeMap<X, usize>)>,
+    tags: &mut Vec<T>,
     reverse: &mut BTreeMap<D::State, usize>,
     state: &D::State,
   ) -> usize
@@ -29,13 +51,14 @@ where
   {
     let id = states.len();
     reverse.insert(state.clone(), id);
-    states.push((dfa.accept(&state), BTreeMap::new()));
+    states.push((dfa.accept(state), BTreeMap::new()));
+    tags.push(tag(state));
     for char in alphabet.clone() {
       if let Some(next) = dfa.next(state.clone(), char.clone()) {
         let next = reverse
           .get(&next)
           .copied()
-          .unwrap_or_else(|| visit(dfa, alphabet.clone(), states, reverse, &next));
+          .unwrap_or_else(|| visit(dfa, alphabet.clone(), tag, states, tags, reverse, &next));
         states.get_mut(id).unwrap().1.insert(char.clone(), next);
       }
     }
@@ -43,6 +66,27 @@ where
   }
 }
 
+pub fn bake_classes<D: Dfa<u8>>(dfa: D, classes: &ByteClasses) -> BakedDfa<u8>
+where
+  D::State: Clone + Ord,
+{
+  let mut baked = bake_dfa(dfa, &classes.representatives());
+  for (_, next) in &mut baked.0 {
+    *next = (0..=u8::MAX)
+      .filter_map(|x| Some((x, *next.get(&classes.representative(x))?)))
+      .collect();
+  }
+  baked
+}
+
+pub fn bake_bytes<D: Dfa<u8> + Classify>(dfa: D) -> BakedDfa<u8>
+where
+  D::State: Clone + Ord,
+{
+  let classes = byte_classes(&dfa);
+  bake_classes(dfa, &classes)
+}
+
 impl<X: Ord> Dfa<X> for BakedDfa<X> {
   type State = usize;
   fn initial(&self) -> Self::State {
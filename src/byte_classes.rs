@@ -0,0 +1,259 @@
+use crate::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ByteClasses(pub [u8; 256]);
+
+impl Default for ByteClasses {
+  fn default() -> Self {
+    ByteClasses([0; 256])
+  }
+}
+
+impl ByteClasses {
+  pub fn split(&mut self, class: Class) {
+    let mut ids = BTreeMap::new();
+    for byte in 0..=u8::MAX {
+      let key = (self.0[byte as usize], class.contains(byte));
+      let id = ids.len() as u8;
+      self.0[byte as usize] = *ids.entry(key).or_insert(id);
+    }
+  }
+
+  pub fn split_byte(&mut self, byte: u8) {
+    self.split(Class::EMPTY.with(byte))
+  }
+
+  pub fn representatives(&self) -> Vec<u8> {
+    let mut seen = BTreeSet::new();
+    (0..=u8::MAX)
+      .filter(|&x| seen.insert(self.0[x as usize]))
+      .collect()
+  }
+
+  pub fn representative(&self, byte: u8) -> u8 {
+    (0..=u8::MAX)
+      .find(|&x| self.0[x as usize] == self.0[byte as usize])
+      .unwrap()
+  }
+
+  pub fn members(&self, byte: u8) -> Class {
+    (0..=u8::MAX)
+      .filter(|&x| self.0[x as usize] == self.0[byte as usize])
+      .fold(Class::EMPTY, Class::with)
+  }
+}
+
+pub trait Classify {
+  fn classify(&self, classes: &mut ByteClasses);
+}
+
+pub fn byte_classes(x: &impl Classify) -> ByteClasses {
+  let mut classes = ByteClasses::default();
+  x.classify(&mut classes);
+  classes
+}
+
+pub fn infer_alphabet(x: &impl Classify) -> Vec<u8> {
+  byte_classes(x).representatives()
+}
+
+impl<T: Classify + ?Sized> Classify for &T {
+  fn classify(&self, classes: &mut ByteClasses) {
+    (*self).classify(classes)
+  }
+}
+
+impl Classify for [u8] {
+  fn classify(&self, classes: &mut ByteClasses) {
+    for &byte in self {
+      classes.split_byte(byte);
+    }
+  }
+}
+
+impl<const N: usize> Classify for [u8; N] {
+  fn classify(&self, classes: &mut ByteClasses) {
+    self[..].classify(classes)
+  }
+}
+
+impl Classify for Class {
+  fn classify(&self, classes: &mut ByteClasses) {
+    classes.split(*self)
+  }
+}
+
+impl Classify for Utf8Class {
+  fn classify(&self, classes: &mut ByteClasses) {
+    for &(from, to) in self.sequences.iter().flatten() {
+      classes.split(Class::EMPTY.with_range(from, to));
+    }
+  }
+}
+
+macro_rules! classify_leaf {
+  ($($t:ty),*) => {
+    $(
+      impl Classify for $t {
+        fn classify(&self, _: &mut ByteClasses) {}
+      }
+    )*
+  };
+}
+
+classify_leaf!(
+  Empty,
+  Anything,
+  Dot,
+  Start,
+  End,
+  AtOffset,
+  AtOffsetFromEnd,
+  AtOffsetMod
+);
+
+fn classify_line(crlf: bool, classes: &mut ByteClasses) {
+  classes.split_byte(b'\n');
+  if crlf {
+    classes.split_byte(b'\r');
+  }
+}
+
+impl Classify for LineStart {
+  fn classify(&self, classes: &mut ByteClasses) {
+    classify_line(self.crlf, classes)
+  }
+}
+
+impl Classify for LineEnd {
+  fn classify(&self, classes: &mut ByteClasses) {
+    classify_line(self.crlf, classes)
+  }
+}
+
+impl Classify for WordBoundary {
+  fn classify(&self, classes: &mut ByteClasses) {
+    classes.split(Class::WORD)
+  }
+}
+
+impl Classify for NotWordBoundary {
+  fn classify(&self, classes: &mut ByteClasses) {
+    classes.split(Class::WORD)
+  }
+}
+
+macro_rules! classify_unary {
+  ($($t:ident),*) => {
+    $(
+      impl<A: Classify> Classify for $t<A> {
+        fn classify(&self, classes: &mut ByteClasses) {
+          self.0.classify(classes)
+        }
+      }
+    )*
+  };
+}
+
+classify_unary!(Not, Plus, LookAhead, LookBehind, FromDfa, ToDfa);
+
+macro_rules! classify_binary {
+  ($($t:ident),*) => {
+    $(
+      impl<A: Classify, B: Classify> Classify for $t<A, B> {
+        fn classify(&self, classes: &mut ByteClasses) {
+          self.0.classify(classes);
+          self.1.classify(classes)
+        }
+      }
+    )*
+  };
+}
+
+classify_binary!(Or, And, Iff, Concat);
+
+impl<A: Classify> Classify for Group<A> {
+  fn classify(&self, classes: &mut ByteClasses) {
+    self.1.classify(classes)
+  }
+}
+
+impl<A: Classify> Classify for Repeat<A> {
+  fn classify(&self, classes: &mut ByteClasses) {
+    self.inner.classify(classes)
+  }
+}
+
+impl Classify for Expr {
+  fn classify(&self, classes: &mut ByteClasses) {
+    match self {
+      Expr::Empty
+      | Expr::Anything
+      | Expr::Dot
+      | Expr::Start
+      | Expr::End
+      | Expr::AtOffset(_)
+      | Expr::AtOffsetFromEnd(_)
+      | Expr::AtOffsetMod(..) => {}
+      Expr::LineStart(crlf) | Expr::LineEnd(crlf) => classify_line(*crlf, classes),
+      Expr::WordBoundary | Expr::NotWordBoundary => classes.split(Class::WORD),
+      Expr::Class(x) => x.classify(classes),
+      Expr::Utf8Class(x) => x.classify(classes),
+      Expr::Literal(x) => x.classify(classes),
+      Expr::Not(a)
+      | Expr::Plus(a)
+      | Expr::Repeat(a, ..)
+      | Expr::LookAhead(a)
+      | Expr::LookBehind(a)
+      | Expr::FromDfa(a)
+      | Expr::ToDfa(a)
+      | Expr::Group(_, a) => a.classify(classes),
+      Expr::Or(a, b) | Expr::And(a, b) | Expr::Iff(a, b) | Expr::Concat(a, b) => {
+        a.classify(classes);
+        b.classify(classes)
+      }
+    }
+  }
+}
+
+impl Classify for BakedDfa<u8> {
+  fn classify(&self, classes: &mut ByteClasses) {
+    for (_, next) in &self.0 {
+      let mut targets = BTreeMap::<usize, Class>::new();
+      for (&byte, &to) in next {
+        let class = targets.entry(to).or_insert(Class::EMPTY);
+        *class = class.with(byte);
+      }
+      for class in targets.into_values() {
+        classes.split(class);
+      }
+    }
+  }
+}
+
+impl Classify for DenseDfa {
+  fn classify(&self, classes: &mut ByteClasses) {
+    let dense = ByteClasses(self.classes);
+    for byte in dense.representatives() {
+      classes.split(dense.members(byte));
+    }
+  }
+}
+
+// Nothing is known about what an erased automaton distinguishes, so every
+// byte gets a class of its own.
+impl<X> Classify for DynDfa<'_, X> {
+  fn classify(&self, classes: &mut ByteClasses) {
+    for byte in 0..=u8::MAX {
+      classes.split_byte(byte);
+    }
+  }
+}
+
+impl<X> Classify for DynThreeFA<'_, X> {
+  fn classify(&self, classes: &mut ByteClasses) {
+    for byte in 0..=u8::MAX {
+      classes.split_byte(byte);
+    }
+  }
+}
@@ -0,0 +1,524 @@
+use crate::*;
+use std::cell::OnceCell;
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Captures {
+  pub spans: Vec<Option<Range<usize>>>,
+  pub names: Vec<Option<String>>,
+}
+
+impl Captures {
+  pub fn get(&self, index: usize) -> Option<Range<usize>> {
+    self.spans.get(index).cloned().flatten()
+  }
+
+  pub fn name(&self, name: &str) -> Option<Range<usize>> {
+    self.get(self.names.iter().position(|x| x.as_deref() == Some(name))?)
+  }
+}
+
+// The overall span is the leftmost-longest one from `find`. Within it the
+// groups are assigned by a tagged automaton that prefers earlier
+// alternatives and more iterations, so a group under `Plus` or `Repeat`
+// reports its last iteration. Subexpressions without groups, as well as
+// `Not` and `Iff`, are never walked into; they run as 3FAs against their
+// phases over the whole haystack, which keeps lookarounds exact.
+pub fn captures(pattern: &Expr, haystack: &[u8]) -> Option<Captures> {
+  let span = find(pattern, haystack)?;
+  let mut names = vec![None];
+  let mut groups = BTreeMap::new();
+  each_node(pattern, &mut vec![], &mut |x, _| {
+    if let Expr::Group(name, _) = x {
+      groups.insert(x as *const Expr, names.len());
+      names.push(name.clone());
+    }
+  });
+  let tagger = Tagger::new(pattern, haystack, groups, false);
+  let mut spans = tagger.tag(&[pattern], span.clone());
+  spans.resize(names.len(), None);
+  spans[0] = Some(span);
+  Some(Captures { spans, names })
+}
+
+pub(crate) type Spans = Vec<Option<Range<usize>>>;
+
+// Where a thread is in the pattern. `Hold` and `Wait` run a subexpression
+// that is not walked into as a 3FA, `Loop` is before another iteration of a
+// repetition, and `Skip` and `Tail` consume the context around the operand
+// of a `ToDfa`. `Wait`, `SkipAny` and `TailAny` consume the next byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Pc {
+  Enter(usize),
+  Exit(usize),
+  Hold(usize),
+  Wait(usize),
+  Loop(usize),
+  Skip(usize),
+  SkipAny(usize),
+  Tail(usize),
+  TailAny(usize),
+  Match,
+}
+
+// The part of a thread that decides its future; of two threads with the
+// same key only the preferred one is kept.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Key {
+  pc: Pc,
+  // The active state of the 3FA run by `Hold` and `Wait`.
+  wait: Option<Value>,
+  // Per repetition: the iterations still required, the iterations still
+  // allowed, and whether the current iteration has consumed anything.
+  counts: Vec<(usize, Option<usize>, bool)>,
+  // Per `And`: the active state of its right operand.
+  shadows: Vec<Option<Value>>,
+}
+
+#[derive(Debug, Clone)]
+struct Thread {
+  key: Key,
+  starts: Vec<usize>,
+  spans: Spans,
+  // Per lookaround and `And`: where it last matched, so that the spans
+  // inside the operand that is not walked can be found for the winner.
+  deferred: Vec<Option<Range<usize>>>,
+}
+
+impl Thread {
+  fn at(mut self, pc: Pc) -> Self {
+    self.key.pc = pc;
+    self
+  }
+}
+
+const IDLE: (usize, Option<usize>, bool) = (0, None, true);
+
+// A tagged automaton over the pattern, run like a Pike VM: threads walk the
+// tree wherever a tracked subexpression can be reached, in order of
+// preference, and record the span each tracked node matched. Everything
+// else runs as a 3FA from its pre state where it starts and may end where
+// its post state accepts the rest of the haystack, so the work per byte is
+// bounded by the size of the pattern. With `fresh`, a repetition clears the
+// spans inside it on every iteration, so they all come from the last one;
+// otherwise, like groups in most regex engines, a node keeps its span from
+// the last iteration that reached it.
+pub(crate) struct Tagger<'a> {
+  haystack: Vec<&'a u8>,
+  nodes: Vec<&'a Expr>,
+  ids: BTreeMap<*const Expr, usize>,
+  // The nodes are numbered in preorder, so the nodes below `i` are the
+  // `sizes[i] - 1` that follow it.
+  parents: Vec<Option<(usize, usize)>>,
+  sizes: Vec<usize>,
+  tracked: Vec<Option<usize>>,
+  inner: Vec<bool>,
+  slots: usize,
+  fresh: bool,
+  phases: Vec<OnceCell<Phases<Expr, &'a u8>>>,
+}
+
+impl<'a> Tagger<'a> {
+  pub(crate) fn new(
+    pattern: &'a Expr,
+    haystack: &'a [u8],
+    tracked: BTreeMap<*const Expr, usize>,
+    fresh: bool,
+  ) -> Self {
+    let mut nodes = vec![];
+    let mut parents = vec![];
+    let mut todo = vec![(pattern, None)];
+    while let Some((expr, parent)) = todo.pop() {
+      let id = nodes.len();
+      nodes.push(expr);
+      parents.push(parent);
+      for (i, child) in children(expr).into_iter().enumerate().rev() {
+        todo.push((child, Some((id, i))));
+      }
+    }
+    let mut sizes = vec![1; nodes.len()];
+    for i in (0..nodes.len()).rev() {
+      if let Some((parent, _)) = parents[i] {
+        sizes[parent] += sizes[i];
+      }
+    }
+    let mut inner = BTreeSet::new();
+    mark(pattern, &tracked, &mut inner);
+    Tagger {
+      haystack: haystack.iter().collect(),
+      ids: nodes
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| (x as *const Expr, i))
+        .collect(),
+      parents,
+      sizes,
+      tracked: nodes
+        .iter()
+        .map(|&x| tracked.get(&(x as *const Expr)).copied())
+        .collect(),
+      inner: nodes
+        .iter()
+        .map(|&x| inner.contains(&(x as *const Expr)))
+        .collect(),
+      slots: tracked.values().map(|x| x + 1).max().unwrap_or(0),
+      fresh,
+      phases: nodes.iter().map(|_| OnceCell::new()).collect(),
+      nodes,
+    }
+  }
+
+  // The spans of the preferred way `parts` in sequence match `span`.
+  pub(crate) fn tag(&self, parts: &[&Expr], span: Range<usize>) -> Spans {
+    self
+      .run(parts, span.start, Some(span.end))
+      .unwrap_or_else(|| vec![None; self.slots])
+  }
+
+  // Runs `parts` from `start`, either to `end` or, without one, to the end
+  // of the preferred match.
+  fn run(&self, parts: &[&Expr], start: usize, end: Option<usize>) -> Option<Spans> {
+    let roots = parts.iter().map(|&x| self.id(x)).collect::<Vec<_>>();
+    let n = self.nodes.len();
+    let mut list = vec![Thread {
+      key: Key {
+        pc: roots.first().map_or(Pc::Match, |&x| Pc::Enter(x)),
+        wait: None,
+        counts: vec![IDLE; n],
+        shadows: vec![None; n],
+      },
+      starts: vec![0; n],
+      spans: vec![None; self.slots],
+      deferred: vec![None; n],
+    }];
+    let mut best = None;
+    for pos in start..=end.unwrap_or(self.haystack.len()) {
+      let mut seen = BTreeSet::new();
+      let mut waiting = vec![];
+      'list: for thread in list {
+        let mut stack = vec![thread];
+        while let Some(thread) = stack.pop() {
+          if !seen.insert(thread.key.clone()) {
+            continue;
+          }
+          match thread.key.pc {
+            // Threads after the first to match are less preferred.
+            Pc::Match if end.is_none_or(|x| x == pos) => {
+              best = Some(thread);
+              break 'list;
+            }
+            Pc::Match => {}
+            Pc::Wait(_) | Pc::SkipAny(_) | Pc::TailAny(_) => waiting.push(thread),
+            _ => stack.extend(self.follow(&roots, thread, pos).into_iter().rev()),
+          }
+        }
+      }
+      if (end.is_some() && best.is_some()) || pos == self.haystack.len() {
+        break;
+      }
+      let char = self.haystack[pos];
+      list = waiting
+        .into_iter()
+        .filter_map(|x| self.consume(x, char))
+        .collect();
+      if list.is_empty() {
+        break;
+      }
+    }
+    best.map(|x| self.resolve(x))
+  }
+
+  // The threads a thread moves to without consuming, most preferred first.
+  fn follow(&self, roots: &[usize], mut thread: Thread, pos: usize) -> Vec<Thread> {
+    match thread.key.pc {
+      Pc::Enter(i) => self.enter(thread, i, pos),
+      Pc::Exit(i) => self.exit(roots, thread, i, pos),
+      Pc::Hold(i) => {
+        let state = thread.key.wait.clone().unwrap();
+        let mut next = vec![thread.clone().at(Pc::Wait(i))];
+        if self.ends_at(i, state, pos) {
+          thread.key.wait = None;
+          next.push(thread.at(Pc::Exit(i)));
+        }
+        next
+      }
+      Pc::Loop(i) => {
+        let (min, max, _) = thread.key.counts[i];
+        if max == Some(0) {
+          return vec![thread.at(Pc::Exit(i))];
+        }
+        let mut again = thread.clone();
+        again.key.counts[i].2 = false;
+        if self.fresh {
+          self.clear(&mut again, i + 1);
+        }
+        let mut next = vec![again.at(Pc::Enter(i + 1))];
+        if min == 0 {
+          next.push(thread.at(Pc::Exit(i)));
+        }
+        next
+      }
+      Pc::Skip(i) => vec![
+        thread.clone().at(Pc::Enter(i + 1)),
+        thread.at(Pc::SkipAny(i)),
+      ],
+      Pc::Tail(i) => vec![thread.clone().at(Pc::TailAny(i)), thread.at(Pc::Exit(i))],
+      _ => unreachable!(),
+    }
+  }
+
+  fn enter(&self, mut thread: Thread, i: usize, pos: usize) -> Vec<Thread> {
+    thread.starts[i] = pos;
+    if !self.inner[i] {
+      return self.hold(thread, i, pos);
+    }
+    match self.nodes[i] {
+      Expr::Group(..) | Expr::FromDfa(_) | Expr::Concat(..) => {
+        vec![thread.at(Pc::Enter(i + 1))]
+      }
+      // `x?` and `x*` are `Or(Empty, ..)`, and are greedy.
+      Expr::Or(a, _) if optional(a) => vec![
+        thread.clone().at(Pc::Enter(self.second(i))),
+        thread.at(Pc::Enter(i + 1)),
+      ],
+      Expr::Or(..) => vec![
+        thread.clone().at(Pc::Enter(i + 1)),
+        thread.at(Pc::Enter(self.second(i))),
+      ],
+      Expr::And(..) => {
+        let Some(state) = self.start_at(self.second(i), pos) else {
+          return vec![];
+        };
+        thread.key.shadows[i] = Some(state);
+        vec![thread.at(Pc::Enter(i + 1))]
+      }
+      Expr::Plus(_) => {
+        thread.key.counts[i] = (1, None, true);
+        vec![thread.at(Pc::Loop(i))]
+      }
+      Expr::Repeat(_, min, max) => {
+        thread.key.counts[i] = (*min, *max, true);
+        vec![thread.at(Pc::Loop(i))]
+      }
+      // Only whether a lookaround holds matters to the rest of the match;
+      // the spans inside it are found once the winning thread is known.
+      Expr::LookAhead(_) | Expr::LookBehind(_) => match self.start_at(i, pos) {
+        Some(state) if self.ends_at(i, state.clone(), pos) => {
+          thread.deferred[i] = Some(pos..pos);
+          vec![thread.at(Pc::Exit(i))]
+        }
+        _ => vec![],
+      },
+      Expr::ToDfa(_) => vec![thread.at(Pc::Skip(i))],
+      _ => self.hold(thread, i, pos),
+    }
+  }
+
+  fn hold(&self, mut thread: Thread, i: usize, pos: usize) -> Vec<Thread> {
+    match self.start_at(i, pos) {
+      Some(state) => {
+        thread.key.wait = Some(state);
+        vec![thread.at(Pc::Hold(i))]
+      }
+      None => vec![],
+    }
+  }
+
+  fn exit(&self, roots: &[usize], mut thread: Thread, i: usize, pos: usize) -> Vec<Thread> {
+    if let Some(slot) = self.tracked[i] {
+      thread.spans[slot] = Some(thread.starts[i]..pos);
+    }
+    if matches!(self.nodes[i], Expr::Plus(_) | Expr::Repeat(..)) {
+      thread.key.counts[i] = IDLE;
+    }
+    if let Some(j) = roots.iter().position(|&x| x == i) {
+      let next = roots.get(j + 1).map_or(Pc::Match, |&x| Pc::Enter(x));
+      return vec![thread.at(next)];
+    }
+    let (parent, child) = self.parents[i].unwrap();
+    match self.nodes[parent] {
+      Expr::Concat(..) if child == 0 => vec![thread.at(Pc::Enter(self.second(parent)))],
+      Expr::And(..) => {
+        let state = thread.key.shadows[parent].take().unwrap();
+        if !self.ends_at(self.second(parent), state, pos) {
+          return vec![];
+        }
+        thread.deferred[parent] = Some(thread.starts[parent]..pos);
+        vec![thread.at(Pc::Exit(parent))]
+      }
+      Expr::Plus(_) | Expr::Repeat(..) => {
+        let (min, max, consumed) = thread.key.counts[parent];
+        let mut next = vec![];
+        if consumed || min > 1 {
+          let mut again = thread.clone();
+          again.key.counts[parent] = (min.saturating_sub(1), max.map(|x| x - 1), consumed);
+          next.push(again.at(Pc::Loop(parent)));
+        }
+        if min <= 1 {
+          next.push(thread.at(Pc::Exit(parent)));
+        }
+        next
+      }
+      Expr::ToDfa(_) => vec![thread.at(Pc::Tail(parent))],
+      _ => vec![thread.at(Pc::Exit(parent))],
+    }
+  }
+
+  fn consume(&self, mut thread: Thread, char: &'a u8) -> Option<Thread> {
+    for x in &mut thread.key.counts {
+      x.2 = true;
+    }
+    for (i, x) in thread.key.shadows.iter_mut().enumerate() {
+      if let Some(state) = x.take() {
+        *x = Some(self.step(self.second(i), state, char)?);
+      }
+    }
+    let pc = match thread.key.pc {
+      Pc::Wait(i) => {
+        let state = thread.key.wait.take()?;
+        thread.key.wait = Some(self.step(i, state, char)?);
+        Pc::Hold(i)
+      }
+      Pc::SkipAny(i) => Pc::Skip(i),
+      Pc::TailAny(i) => Pc::Tail(i),
+      _ => unreachable!(),
+    };
+    Some(thread.at(pc))
+  }
+
+  fn clear(&self, thread: &mut Thread, i: usize) {
+    for j in i..i + self.sizes[i] {
+      if let Some(slot) = self.tracked[j] {
+        thread.spans[slot] = None;
+      }
+      thread.deferred[j] = None;
+    }
+  }
+
+  // Fills in the spans inside the lookarounds and the right operands of
+  // `And` that the winning thread went through.
+  fn resolve(&self, thread: Thread) -> Spans {
+    let mut spans = thread.spans;
+    for (i, range) in thread.deferred.into_iter().enumerate() {
+      let Some(range) = range else {
+        continue;
+      };
+      let inside = match self.nodes[i] {
+        Expr::LookAhead(a) => self.run(&[a], range.start, None),
+        Expr::LookBehind(a) => self
+          .first_start(i + 1, range.start)
+          .and_then(|start| self.run(&[a], start, Some(range.start))),
+        Expr::And(_, b) => self.run(&[b], range.start, Some(range.end)),
+        _ => None,
+      };
+      for (x, y) in spans.iter_mut().zip(inside.into_iter().flatten()) {
+        if y.is_some() {
+          *x = y;
+        }
+      }
+    }
+    spans
+  }
+
+  // The leftmost offset from which node `i` matches up to `end`.
+  fn first_start(&self, i: usize, end: usize) -> Option<usize> {
+    let mut active = BTreeMap::<Value, usize>::new();
+    for pos in 0..=end {
+      if let Some(state) = self.start_at(i, pos) {
+        active.entry(state).or_insert(pos);
+      }
+      if pos == end {
+        break;
+      }
+      let mut next = BTreeMap::new();
+      for (state, start) in active {
+        if let Some(state) = self.step(i, state, self.haystack[pos]) {
+          next
+            .entry(state)
+            .and_modify(|x: &mut usize| *x = start.min(*x))
+            .or_insert(start);
+        }
+      }
+      active = next;
+    }
+    active
+      .into_iter()
+      .filter(|(state, _)| self.ends_at(i, state.clone(), end))
+      .map(|(_, start)| start)
+      .min()
+  }
+
+  fn id(&self, expr: &Expr) -> usize {
+    self.ids[&(expr as *const Expr)]
+  }
+
+  fn second(&self, i: usize) -> usize {
+    i + 1 + self.sizes[i + 1]
+  }
+
+  fn phases(&self, i: usize) -> &Phases<Expr, &'a u8> {
+    self.phases[i].get_or_init(|| phases(self.nodes[i], &self.haystack))
+  }
+
+  fn start_at(&self, i: usize, pos: usize) -> Option<Value> {
+    let pre = self.phases(i).0[pos].clone()?;
+    ThreeFA::<&u8>::enter(self.nodes[i], pre)
+  }
+
+  fn step(&self, i: usize, state: Value, char: &'a u8) -> Option<Value> {
+    ThreeFA::<&u8>::step_active(self.nodes[i], state, char)
+  }
+
+  fn ends_at(&self, i: usize, state: Value, pos: usize) -> bool {
+    ThreeFA::<&u8>::exit(self.nodes[i], state).is_some_and(|x| self.phases(i).1[pos].contains(&x))
+  }
+}
+
+fn optional(expr: &Expr) -> bool {
+  match expr {
+    Expr::Empty => true,
+    Expr::FromDfa(a) => optional(a),
+    _ => false,
+  }
+}
+
+pub(crate) fn children(expr: &Expr) -> Vec<&Expr> {
+  match expr {
+    Expr::Group(_, a)
+    | Expr::Not(a)
+    | Expr::Plus(a)
+    | Expr::Repeat(a, ..)
+    | Expr::LookAhead(a)
+    | Expr::LookBehind(a)
+    | Expr::FromDfa(a)
+    | Expr::ToDfa(a) => vec![a],
+    Expr::Or(a, b) | Expr::And(a, b) | Expr::Iff(a, b) | Expr::Concat(a, b) => vec![a, b],
+    _ => vec![],
+  }
+}
+
+pub(crate) fn each_node(expr: &Expr, path: &mut Vec<usize>, f: &mut dyn FnMut(&Expr, &[usize])) {
+  f(expr, path);
+  for (i, child) in children(expr).into_iter().enumerate() {
+    path.push(i);
+    each_node(child, path, f);
+    path.pop();
+  }
+}
+
+// Marks the nodes that contain a tracked node and are not under `Not` or
+// `Iff`; only those are walked into.
+fn mark(
+  expr: &Expr,
+  tracked: &BTreeMap<*const Expr, usize>,
+  inner: &mut BTreeSet<*const Expr>,
+) -> bool {
+  let mut found = tracked.contains_key(&(expr as *const Expr));
+  for child in children(expr) {
+    found |= mark(child, tracked, inner);
+  }
+  if found && !matches!(expr, Expr::Not(_) | Expr::Iff(..)) {
+    inner.insert(expr);
+  }
+  found
+}
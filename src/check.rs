@@ -0,0 +1,76 @@
+use crate::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchError {
+  pub offset: usize,
+  pub found: Option<u8>,
+  pub expected: Class,
+  pub continuation: Option<Vec<u8>>,
+}
+
+impl std::fmt::Display for MatchError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let found = match self.found {
+      Some(x) => format!("`{}`", byte_label(x)),
+      None => "end of input".to_owned(),
+    };
+    if self.expected == Class::EMPTY {
+      write!(f, "unexpected {found} at offset {}", self.offset)
+    } else {
+      let expected = class_label(self.expected);
+      write!(
+        f,
+        "expected `{expected}` but found {found} at offset {}",
+        self.offset
+      )
+    }
+  }
+}
+
+impl std::error::Error for MatchError {}
+
+// Reports the first byte after which the input can no longer be completed to
+// a match, or the end of the input if it is a proper prefix of one.
+pub fn check<D: Dfa<u8>>(dfa: D, input: &[u8]) -> Result<(), MatchError>
+where
+  D::State: Clone + Ord,
+{
+  let bytes = 0..=u8::MAX;
+  let mut live = BTreeMap::new();
+  let mut is_live = |state: &D::State| {
+    *live
+      .entry(state.clone())
+      .or_insert_with(|| is_empty_from(&dfa, state.clone(), bytes.clone()).is_err())
+  };
+  let mut state = dfa.initial();
+  let mut offset = 0;
+  let mut found = None;
+  if is_live(&state) {
+    for &char in input {
+      match dfa.next(state.clone(), char) {
+        Some(next) if is_live(&next) => state = next,
+        _ => {
+          found = Some(char);
+          break;
+        }
+      }
+      offset += 1;
+    }
+    if found.is_none() && dfa.accept(&state) {
+      return Ok(());
+    }
+  } else {
+    found = input.first().copied();
+  }
+  let expected = bytes
+    .clone()
+    .filter(|&x| dfa.next(state.clone(), x).is_some_and(|x| is_live(&x)))
+    .fold(Class::EMPTY, Class::with);
+  let continuation = is_empty_from(&dfa, state, bytes).err();
+  Err(MatchError {
+    offset,
+    found,
+    expected,
+    continuation,
+  })
+}
@@ -0,0 +1,112 @@
+use crate::*;
+
+#[derive(Debug, Clone)]
+pub struct Decided<D, S> {
+  pub dfa: D,
+  pub outcomes: BTreeMap<S, bool>,
+}
+
+// Explores every state reachable over `alphabet` and records those whose
+// outcome no longer depends on the rest of the input: `false` for states
+// that cannot reach acceptance, `true` for states that accept whatever
+// follows. The alphabet has to cover every symbol the input may contain.
+pub fn decided<X: Clone, D: Dfa<X>>(
+  dfa: D,
+  alphabet: impl Clone + IntoIterator<Item = X>,
+) -> Decided<D, D::State>
+where
+  D::State: Clone + Ord,
+{
+  let (states, edges) = explore(&dfa, dfa.initial(), alphabet);
+  let live = live(&dfa, &states, &edges);
+  let mut forever = states.iter().map(|x| dfa.accept(x)).collect::<Vec<_>>();
+  let mut changed = true;
+  while changed {
+    changed = false;
+    for (i, next) in edges.iter().enumerate() {
+      if forever[i] && next.iter().any(|x| !x.is_some_and(|x| forever[x])) {
+        forever[i] = false;
+        changed = true;
+      }
+    }
+  }
+
+  let outcomes = states
+    .into_iter()
+    .enumerate()
+    .filter(|&(i, _)| !live[i] || forever[i])
+    .map(|(i, x)| (x, live[i]))
+    .collect();
+  Decided { dfa, outcomes }
+}
+
+impl<X, D: Dfa<X, State = S>, S: Finite + Ord> Dfa<X> for Decided<D, S> {
+  type State = S;
+  fn initial(&self) -> Self::State {
+    self.dfa.initial()
+  }
+  fn next(&self, state: Self::State, char: X) -> Option<Self::State> {
+    self.dfa.next(state, char)
+  }
+  fn accept(&self, state: &Self::State) -> bool {
+    self.dfa.accept(state)
+  }
+  fn decide(&self, state: &Self::State) -> Option<bool> {
+    self
+      .outcomes
+      .get(state)
+      .copied()
+      .or_else(|| self.dfa.decide(state))
+  }
+}
+
+pub(crate) type Graph<S> = (Vec<S>, Vec<Vec<Option<usize>>>);
+
+// Numbers the states reachable from `state` in the order they are found,
+// with `state` as 0, and records for each the successor on every symbol.
+pub(crate) fn explore<X: Clone, D: Dfa<X>>(
+  dfa: &D,
+  state: D::State,
+  alphabet: impl Clone + IntoIterator<Item = X>,
+) -> Graph<D::State>
+where
+  D::State: Clone + Ord,
+{
+  let mut ids = BTreeMap::new();
+  ids.insert(state.clone(), 0);
+  let mut states = vec![state];
+  let mut edges = vec![];
+  while let Some(state) = states.get(edges.len()).cloned() {
+    let mut next = vec![];
+    for char in alphabet.clone() {
+      next.push(dfa.next(state.clone(), char).map(|x| {
+        *ids.entry(x.clone()).or_insert_with(|| {
+          states.push(x);
+          states.len() - 1
+        })
+      }));
+    }
+    edges.push(next);
+  }
+  (states, edges)
+}
+
+// Which of the explored states can still reach acceptance.
+pub(crate) fn live<X, D: Dfa<X>>(
+  dfa: &D,
+  states: &[D::State],
+  edges: &[Vec<Option<usize>>],
+) -> Vec<bool> {
+  let mut live = states.iter().map(|x| dfa.accept(x)).collect::<Vec<_>>();
+  let mut changed = true;
+  while changed {
+    changed = false;
+    for (i, next) in edges.iter().enumerate() {
+      if !live[i] && next.iter().flatten().any(|&x| live[x]) {
+        live[i] = true;
+        changed = true;
+      }
+    }
+  }
+  live
+}
@@ -0,0 +1,95 @@
+use crate::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DenseDfa {
+  pub classes: [u8; 256],
+  pub stride: usize,
+  pub table: Vec<u32>,
+  pub accept: Vec<bool>,
+}
+
+impl DenseDfa {
+  pub const DEAD: u32 = 0;
+  pub const INITIAL: u32 = 1;
+}
+
+pub fn dense_dfa(dfa: &BakedDfa<u8>) -> DenseDfa {
+  let states = &dfa.0;
+
+  let mut signatures = BTreeMap::new();
+  let mut classes = [0; 256];
+  let mut representatives = vec![];
+  for byte in 0..=u8::MAX {
+    let signature = states
+      .iter()
+      .map(|x| x.1.get(&byte).copied())
+      .collect::<Vec<_>>();
+    classes[byte as usize] = *signatures.entry(signature).or_insert_with(|| {
+      representatives.push(byte);
+      (representatives.len() - 1) as u8
+    });
+  }
+  let stride = representatives.len();
+
+  let mut ids = vec![None; states.len()];
+  let mut order = vec![];
+  if !states.is_empty() {
+    ids[0] = Some(DenseDfa::INITIAL);
+    order.push(0);
+  }
+  let mut table = vec![DenseDfa::DEAD; stride];
+  let mut accept = vec![false];
+  while let Some(&state) = order.get(accept.len() - 1) {
+    accept.push(states[state].0);
+    for &byte in &representatives {
+      let next = match states[state].1.get(&byte) {
+        Some(&next) => *ids[next].get_or_insert_with(|| {
+          order.push(next);
+          order.len() as u32
+        }),
+        None => DenseDfa::DEAD,
+      };
+      table.push(next);
+    }
+  }
+  if states.is_empty() {
+    accept.push(false);
+    table.extend(std::iter::repeat_n(DenseDfa::DEAD, stride));
+  }
+
+  DenseDfa {
+    classes,
+    stride,
+    table,
+    accept,
+  }
+}
+
+impl Dfa<u8> for DenseDfa {
+  type State = u32;
+  fn initial(&self) -> Self::State {
+    DenseDfa::INITIAL
+  }
+  fn next(&self, state: Self::State, char: u8) -> Option<Self::State> {
+    match self.table[state as usize * self.stride + self.classes[char as usize] as usize] {
+      DenseDfa::DEAD => None,
+      next => Some(next),
+    }
+  }
+  fn accept(&self, state: &Self::State) -> bool {
+    self.accept[*state as usize]
+  }
+}
+
+impl<'a> Dfa<&'a u8> for DenseDfa {
+  type State = u32;
+  fn initial(&self) -> Self::State {
+    DenseDfa::INITIAL
+  }
+  fn next(&self, state: Self::State, char: &'a u8) -> Option<Self::State> {
+    Dfa::<u8>::next(self, state, *char)
+  }
+  fn accept(&self, state: &Self::State) -> bool {
+    self.accept[*state as usize]
+  }
+}
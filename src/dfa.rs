@@ -2,24 +2,31 @@ use crate::*;
 
 mod and;
 mod anything;
+mod class;
 mod concat;
 mod dot;
 mod empty;
 mod from_dfa;
+mod group;
 mod iff;
 mod not;
 mod or;
 mod plus;
+mod repeat;
 mod str;
+mod utf8_class;
 
 pub trait Dfa<X> {
   type State: Finite;
   fn initial(&self) -> Self::State;
   fn next(&self, state: Self::State, char: X) -> Option<Self::State>;
   fn accept(&self, state: &Self::State) -> bool;
+  fn decide(&self, _state: &Self::State) -> Option<bool> {
+    None
+  }
 }
 
-impl<'a, X, T: Dfa<X> + ?Sized> Dfa<X> for &'a T {
+impl<X, T: Dfa<X> + ?Sized> Dfa<X> for &T {
   type State = T::State;
   fn initial(&self) -> Self::State {
     (*self).initial()
@@ -30,4 +37,7 @@ impl<'a, X, T: Dfa<X> + ?Sized> Dfa<X> for &'a T {
   fn accept(&self, state: &Self::State) -> bool {
     (*self).accept(state)
   }
+  fn decide(&self, state: &Self::State) -> Option<bool> {
+    (*self).decide(state)
+  }
 }
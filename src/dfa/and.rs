@@ -14,4 +14,11 @@ impl<X: Clone, A: Dfa<X>, B: Dfa<X>> Dfa<X> for And<A, B> {
   fn accept(&self, state: &Self::State) -> bool {
     self.0.accept(&state.0) && self.1.accept(&state.1)
   }
+  fn decide(&self, state: &Self::State) -> Option<bool> {
+    match (self.0.decide(&state.0), self.1.decide(&state.1)) {
+      (Some(false), _) | (_, Some(false)) => Some(false),
+      (Some(true), Some(true)) => Some(true),
+      _ => None,
+    }
+  }
 }
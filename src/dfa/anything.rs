@@ -2,13 +2,14 @@ use crate::*;
 
 impl<X> Dfa<X> for Anything {
   type State = ();
-  fn initial(&self) -> Self::State {
-    ()
-  }
+  fn initial(&self) -> Self::State {}
   fn next(&self, _: Self::State, _: X) -> Option<Self::State> {
     Some(())
   }
   fn accept(&self, _: &Self::State) -> bool {
     true
   }
+  fn decide(&self, _: &Self::State) -> Option<bool> {
+    Some(true)
+  }
 }
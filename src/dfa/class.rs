@@ -0,0 +1,56 @@
+use crate::*;
+use std::borrow::Borrow;
+
+impl Class {
+  pub const EMPTY: Class = Class([0; 4]);
+  pub const DIGIT: Class = Class::EMPTY.with_range(b'0', b'9');
+  pub const WORD: Class = Class::DIGIT
+    .with_range(b'a', b'z')
+    .with_range(b'A', b'Z')
+    .with(b'_');
+  pub const SPACE: Class = Class::EMPTY.with_range(b'\t', b'\r').with(b' ');
+
+  pub const fn with(mut self, byte: u8) -> Class {
+    self.0[byte as usize / 64] |= 1 << (byte % 64);
+    self
+  }
+
+  pub const fn with_range(mut self, from: u8, to: u8) -> Class {
+    let mut byte = from as usize;
+    while byte <= to as usize {
+      self = self.with(byte as u8);
+      byte += 1;
+    }
+    self
+  }
+
+  pub const fn union(self, other: Class) -> Class {
+    let (a, b) = (self.0, other.0);
+    Class([a[0] | b[0], a[1] | b[1], a[2] | b[2], a[3] | b[3]])
+  }
+
+  pub const fn negate(self) -> Class {
+    let a = self.0;
+    Class([!a[0], !a[1], !a[2], !a[3]])
+  }
+
+  pub const fn contains(&self, byte: u8) -> bool {
+    self.0[byte as usize / 64] & (1 << (byte % 64)) != 0
+  }
+}
+
+impl<X: Borrow<u8>> Dfa<X> for Class {
+  type State = bool;
+  fn initial(&self) -> Self::State {
+    false
+  }
+  fn next(&self, state: Self::State, char: X) -> Option<Self::State> {
+    match state {
+      false if self.contains(*char.borrow()) => Some(true),
+      _ => None,
+    }
+  }
+  fn accept(&self, state: &Self::State) -> bool {
+    *state
+  }
+}
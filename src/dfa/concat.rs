@@ -21,7 +21,7 @@ where
       .into_iter()
       .filter_map(|x| self.1.next(x, char.clone()))
       .chain(state.0.as_ref().and_then(|x| {
-        if self.0.accept(&x) {
+        if self.0.accept(x) {
           Some(self.1.initial())
         } else {
           None
@@ -11,4 +11,7 @@ impl<X, A: Dfa<X>> Dfa<X> for FromDfa<A> {
   fn next(&self, state: Self::State, char: X) -> Option<Self::State> {
     self.0.next(state, char)
   }
+  fn decide(&self, state: &Self::State) -> Option<bool> {
+    self.0.decide(state)
+  }
 }
@@ -0,0 +1,17 @@
+use crate::*;
+
+impl<X, A: Dfa<X>> Dfa<X> for Group<A> {
+  type State = A::State;
+  fn initial(&self) -> Self::State {
+    self.1.initial()
+  }
+  fn next(&self, state: Self::State, char: X) -> Option<Self::State> {
+    self.1.next(state, char)
+  }
+  fn accept(&self, state: &Self::State) -> bool {
+    self.1.accept(state)
+  }
+  fn decide(&self, state: &Self::State) -> Option<bool> {
+    self.1.decide(state)
+  }
+}
@@ -12,7 +12,12 @@ impl<X: Clone, A: Dfa<X>, B: Dfa<X>> Dfa<X> for Iff<A, B> {
     ))
   }
   fn accept(&self, state: &Self::State) -> bool {
-    state.0.as_ref().map_or(false, |x| self.0.accept(x))
-      == state.1.as_ref().map_or(false, |x| self.1.accept(x))
+    state.0.as_ref().is_some_and(|x| self.0.accept(x))
+      == state.1.as_ref().is_some_and(|x| self.1.accept(x))
+  }
+  fn decide(&self, state: &Self::State) -> Option<bool> {
+    let a = state.0.as_ref().map_or(Some(false), |x| self.0.decide(x))?;
+    let b = state.1.as_ref().map_or(Some(false), |x| self.1.decide(x))?;
+    Some(a == b)
   }
 }
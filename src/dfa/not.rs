@@ -9,6 +9,12 @@ impl<X, A: Dfa<X>> Dfa<X> for Not<A> {
     Some(state.and_then(|state| self.0.next(state, char)))
   }
   fn accept(&self, state: &Self::State) -> bool {
-    !state.as_ref().map_or(false, |state| self.0.accept(state))
+    !state.as_ref().is_some_and(|state| self.0.accept(state))
+  }
+  fn decide(&self, state: &Self::State) -> Option<bool> {
+    match state {
+      Some(state) => self.0.decide(state).map(|x| !x),
+      None => Some(true),
+    }
   }
 }
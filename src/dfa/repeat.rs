@@ -0,0 +1,62 @@
+use crate::*;
+
+impl<A> Repeat<A> {
+  pub(crate) fn more(&self, count: usize) -> Option<usize> {
+    match self.max {
+      Some(max) if count + 1 >= max => None,
+      Some(_) => Some(count + 1),
+      None => Some((count + 1).min(self.min.saturating_sub(1))),
+    }
+  }
+
+  fn dfa_closure<X, S: Clone + Ord>(&self, mut state: BTreeSet<(usize, S)>) -> BTreeSet<(usize, S)>
+  where
+    A: Dfa<X, State = S>,
+  {
+    let mut todo = state.iter().cloned().collect::<Vec<_>>();
+    while let Some((n, x)) = todo.pop() {
+      if let Some(n) = self.inner.accept(&x).then(|| self.more(n)).flatten() {
+        let x = (n, self.inner.initial());
+        if state.insert(x.clone()) {
+          todo.push(x);
+        }
+      }
+    }
+    state
+  }
+}
+
+impl<X: Clone, A: Dfa<X>> Dfa<X> for Repeat<A>
+where
+  A::State: Clone + Ord,
+{
+  type State = (bool, BTreeSet<(usize, A::State)>);
+  fn initial(&self) -> Self::State {
+    let state = if self.max == Some(0) {
+      BTreeSet::new()
+    } else {
+      [(0, self.inner.initial())].into_iter().collect()
+    };
+    (true, self.dfa_closure(state))
+  }
+  fn next(&self, state: Self::State, char: X) -> Option<Self::State> {
+    let state = state
+      .1
+      .into_iter()
+      .filter_map(|(n, x)| Some((n, self.inner.next(x, char.clone())?)))
+      .collect();
+    let state = self.dfa_closure(state);
+    if !state.is_empty() {
+      Some((false, state))
+    } else {
+      None
+    }
+  }
+  fn accept(&self, state: &Self::State) -> bool {
+    (state.0 && self.min == 0)
+      || state
+        .1
+        .iter()
+        .any(|(n, x)| n + 1 >= self.min && self.inner.accept(x))
+  }
+}
@@ -40,7 +40,7 @@ impl<'a, C: Eq> Dfa<&'a C> for [C] {
     0
   }
   fn next(&self, state: Self::State, char: &'a C) -> Option<Self::State> {
-    if self.get(state) == Some(&char) {
+    if self.get(state) == Some(char) {
       Some(state + 1)
     } else {
       None
@@ -57,7 +57,7 @@ impl<'a, C: Eq, const N: usize> Dfa<&'a C> for [C; N] {
     0
   }
   fn next(&self, state: Self::State, char: &'a C) -> Option<Self::State> {
-    if self.get(state) == Some(&char) {
+    if self.get(state) == Some(char) {
       Some(state + 1)
     } else {
       None
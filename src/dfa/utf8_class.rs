@@ -0,0 +1,181 @@
+use crate::*;
+use std::borrow::Borrow;
+
+impl Utf8Class {
+  pub fn new(mut ranges: Vec<(char, char)>) -> Utf8Class {
+    ranges.retain(|(from, to)| from <= to);
+    ranges.sort();
+    let mut merged: Vec<(char, char)> = vec![];
+    for (from, to) in ranges {
+      match merged.last_mut() {
+        Some(last) if next_char(last.1).is_none_or(|x| x >= from) => last.1 = last.1.max(to),
+        _ => merged.push((from, to)),
+      }
+    }
+    let mut sequences = vec![];
+    for &(from, to) in &merged {
+      push_sequences(from as u32, to as u32, &mut sequences);
+    }
+    Utf8Class {
+      ranges: merged,
+      sequences,
+    }
+  }
+
+  pub fn any() -> Utf8Class {
+    Utf8Class::new(vec![('\0', char::MAX)])
+  }
+
+  // Bytes stand for the code points U+0000 to U+00FF, except that a class
+  // holding every byte from 0x80 up, like `\W`, holds every non-ASCII code
+  // point.
+  pub fn from_class(class: Class) -> Utf8Class {
+    let mut ranges = (0..=u8::MAX)
+      .filter(|&x| class.contains(x))
+      .map(|x| (x as char, x as char))
+      .collect::<Vec<_>>();
+    if (0x80..=u8::MAX).all(|x| class.contains(x)) {
+      ranges.push(('\u{80}', char::MAX));
+    }
+    Utf8Class::new(ranges)
+  }
+
+  // Names are matched ignoring case, spaces, `_` and `-`, and may be prefixed
+  // by `gc=` or `sc=` (or their long forms) to look only among general
+  // categories or scripts.
+  pub fn property(name: &str) -> Option<Utf8Class> {
+    let loose = |x: &str| {
+      x.chars()
+        .filter(|x| !matches!(x, ' ' | '_' | '-'))
+        .collect::<String>()
+        .to_ascii_lowercase()
+    };
+    let (tables, name): (&[&[Property]], _) = match name.split_once('=') {
+      Some((key, value)) => match &loose(key)[..] {
+        "gc" | "generalcategory" => (&[GENERAL_CATEGORIES], value),
+        "sc" | "script" => (&[SCRIPTS], value),
+        _ => return None,
+      },
+      None => (&[GENERAL_CATEGORIES, SCRIPTS, PROPERTIES], name),
+    };
+    let name = loose(name);
+    let (_, ranges) = tables
+      .iter()
+      .flat_map(|x| x.iter())
+      .find(|(names, _)| names.iter().any(|x| loose(x) == name))?;
+    Some(Utf8Class::new(ranges.to_vec()))
+  }
+
+  pub fn union(&self, other: &Utf8Class) -> Utf8Class {
+    Utf8Class::new([&self.ranges[..], &other.ranges[..]].concat())
+  }
+
+  pub fn negate(&self) -> Utf8Class {
+    let mut ranges = vec![];
+    let mut from = Some('\0');
+    for &(start, end) in &self.ranges {
+      if let Some(x) = from.filter(|&x| x < start) {
+        ranges.push((x, prev_char(start).unwrap()));
+      }
+      from = next_char(end);
+    }
+    if let Some(x) = from {
+      ranges.push((x, char::MAX));
+    }
+    Utf8Class::new(ranges)
+  }
+
+  pub fn contains(&self, char: char) -> bool {
+    self
+      .ranges
+      .iter()
+      .any(|&(from, to)| from <= char && char <= to)
+  }
+}
+
+fn next_char(char: char) -> Option<char> {
+  match char {
+    '\u{D7FF}' => Some('\u{E000}'),
+    x => char::from_u32(x as u32 + 1),
+  }
+}
+
+fn prev_char(char: char) -> Option<char> {
+  match char {
+    '\u{E000}' => Some('\u{D7FF}'),
+    x => char::from_u32((x as u32).checked_sub(1)?),
+  }
+}
+
+// Splits a range of code points into sequences of byte ranges, one per
+// encoded byte, such that the sequences match exactly the encodings of the
+// range. Ranges are split until they have one encoded length and every byte
+// after the first varying one spans all continuation bytes.
+fn push_sequences(from: u32, to: u32, out: &mut Vec<Vec<(u8, u8)>>) {
+  if from <= 0xDFFF && to >= 0xD800 {
+    if from < 0xD800 {
+      push_sequences(from, 0xD7FF, out);
+    }
+    if to > 0xDFFF {
+      push_sequences(0xE000, to, out);
+    }
+    return;
+  }
+  for max in [0x7F, 0x7FF, 0xFFFF] {
+    if from <= max && to > max {
+      push_sequences(from, max, out);
+      push_sequences(max + 1, to, out);
+      return;
+    }
+  }
+  let encode = |x| {
+    let mut buffer = [0; 4];
+    char::from_u32(x)
+      .unwrap()
+      .encode_utf8(&mut buffer)
+      .as_bytes()
+      .to_vec()
+  };
+  let (from_bytes, to_bytes) = (encode(from), encode(to));
+  for i in 1..from_bytes.len() {
+    let mask = (1 << (6 * i)) - 1;
+    if from & !mask != to & !mask {
+      if from & mask != 0 {
+        push_sequences(from, from | mask, out);
+        push_sequences((from | mask) + 1, to, out);
+        return;
+      }
+      if to & mask != mask {
+        push_sequences(from, (to & !mask) - 1, out);
+        push_sequences(to & !mask, to, out);
+        return;
+      }
+    }
+  }
+  out.push(from_bytes.into_iter().zip(to_bytes).collect());
+}
+
+// The state is the number of bytes read and the sequences still matching
+// them.
+impl<X: Borrow<u8>> Dfa<X> for Utf8Class {
+  type State = (usize, BTreeSet<usize>);
+  fn initial(&self) -> Self::State {
+    (0, (0..self.sequences.len()).collect())
+  }
+  fn next(&self, (len, alive): Self::State, char: X) -> Option<Self::State> {
+    let char = *char.borrow();
+    let alive = alive
+      .into_iter()
+      .filter(|&x| {
+        let sequence = &self.sequences[x];
+        sequence
+          .get(len)
+          .is_some_and(|&(from, to)| from <= char && char <= to)
+      })
+      .collect::<BTreeSet<_>>();
+    (!alive.is_empty()).then_some((len + 1, alive))
+  }
+  fn accept(&self, (len, alive): &Self::State) -> bool {
+    alive.iter().any(|&x| self.sequences[x].len() == *len)
+  }
+}
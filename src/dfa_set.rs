@@ -0,0 +1,90 @@
+use crate::*;
+use std::any::Any;
+use std::borrow::Borrow;
+
+pub struct DfaSet<'a, X>(pub Vec<DynDfa<'a, X>>);
+
+impl<'a, X> DfaSet<'a, X> {
+  pub fn new() -> Self {
+    DfaSet(vec![])
+  }
+
+  pub fn push<D: Dfa<X> + 'a>(&mut self, dfa: D) -> usize
+  where
+    D::State: Any + Clone + Ord,
+  {
+    self.0.push(DynDfa::new(dfa));
+    self.0.len() - 1
+  }
+
+  pub fn matching(&self, state: &[Option<DynState>]) -> BTreeSet<usize> {
+    (0..self.0.len())
+      .filter(|&i| state[i].as_ref().is_some_and(|x| self.0[i].accept(x)))
+      .collect()
+  }
+
+  pub fn matches(&self, str: impl IntoIterator<Item = impl Borrow<X>>) -> BTreeSet<usize>
+  where
+    X: Clone,
+  {
+    let mut state = self.initial();
+    for char in str {
+      state = match self.next(state, char.borrow().clone()) {
+        Some(x) => x,
+        None => return BTreeSet::new(),
+      }
+    }
+    self.matching(&state)
+  }
+
+  pub fn bake<'b>(&self, alphabet: impl Clone + IntoIterator<Item = &'b X>) -> BakedDfaSet<X>
+  where
+    X: Clone + Ord + 'b,
+  {
+    let (dfa, accepts) = bake_tagged(self, alphabet, |x| self.matching(x));
+    BakedDfaSet { dfa, accepts }
+  }
+}
+
+impl<X> Default for DfaSet<'_, X> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<X: Clone> Dfa<X> for DfaSet<'_, X> {
+  type State = Vec<Option<DynState>>;
+  fn initial(&self) -> Self::State {
+    self.0.iter().map(|x| Some(x.initial())).collect()
+  }
+  fn next(&self, state: Self::State, char: X) -> Option<Self::State> {
+    let state = state
+      .into_iter()
+      .zip(&self.0)
+      .map(|(x, dfa)| x.and_then(|x| dfa.next(x, char.clone())))
+      .collect::<Vec<_>>();
+    state.iter().any(|x| x.is_some()).then_some(state)
+  }
+  fn accept(&self, state: &Self::State) -> bool {
+    !self.matching(state).is_empty()
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BakedDfaSet<X> {
+  pub dfa: BakedDfa<X>,
+  pub accepts: Vec<BTreeSet<usize>>,
+}
+
+impl<X: Ord> BakedDfaSet<X> {
+  pub fn matches(&self, str: impl IntoIterator<Item = impl Borrow<X>>) -> BTreeSet<usize> {
+    let mut state = 0;
+    for char in str {
+      match self.dfa.0[state].1.get(char.borrow()) {
+        Some(&x) => state = x,
+        None => return BTreeSet::new(),
+      }
+    }
+    self.accepts[state].clone()
+  }
+}
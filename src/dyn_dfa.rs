@@ -0,0 +1,214 @@
+use crate::*;
+use std::any::Any;
+use std::cmp::Ordering;
+
+pub struct DynState(Box<dyn ErasedState>);
+
+trait ErasedState {
+  fn as_any(&self) -> &dyn Any;
+  fn into_any(self: Box<Self>) -> Box<dyn Any>;
+  fn clone_box(&self) -> Box<dyn ErasedState>;
+  fn cmp_dyn(&self, other: &dyn ErasedState) -> Ordering;
+}
+
+impl<T: Any + Clone + Ord> ErasedState for T {
+  fn as_any(&self) -> &dyn Any {
+    self
+  }
+  fn into_any(self: Box<Self>) -> Box<dyn Any> {
+    self
+  }
+  fn clone_box(&self) -> Box<dyn ErasedState> {
+    Box::new(self.clone())
+  }
+  fn cmp_dyn(&self, other: &dyn ErasedState) -> Ordering {
+    match other.as_any().downcast_ref::<T>() {
+      Some(other) => self.cmp(other),
+      None => self.as_any().type_id().cmp(&other.as_any().type_id()),
+    }
+  }
+}
+
+impl DynState {
+  fn new<T: Any + Clone + Ord>(state: T) -> Self {
+    DynState(Box::new(state))
+  }
+  fn get<T: Any>(&self) -> &T {
+    self.0.as_any().downcast_ref().unwrap()
+  }
+  fn take<T: Any>(self) -> T {
+    *self.0.into_any().downcast().unwrap()
+  }
+}
+
+impl Clone for DynState {
+  fn clone(&self) -> Self {
+    DynState(self.0.clone_box())
+  }
+}
+
+impl PartialEq for DynState {
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl Eq for DynState {}
+
+impl PartialOrd for DynState {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for DynState {
+  fn cmp(&self, other: &Self) -> Ordering {
+    self.0.cmp_dyn(&*other.0)
+  }
+}
+
+impl std::fmt::Debug for DynState {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("DynState")
+  }
+}
+
+impl Finite for DynState {}
+
+trait ErasedDfa<X> {
+  fn initial(&self) -> DynState;
+  fn next(&self, state: DynState, char: X) -> Option<DynState>;
+  fn accept(&self, state: &DynState) -> bool;
+  fn decide(&self, state: &DynState) -> Option<bool>;
+}
+
+impl<X, D: Dfa<X>> ErasedDfa<X> for D
+where
+  D::State: Any + Clone + Ord,
+{
+  fn initial(&self) -> DynState {
+    DynState::new(Dfa::initial(self))
+  }
+  fn next(&self, state: DynState, char: X) -> Option<DynState> {
+    Dfa::next(self, state.take(), char).map(DynState::new)
+  }
+  fn accept(&self, state: &DynState) -> bool {
+    Dfa::accept(self, state.get())
+  }
+  fn decide(&self, state: &DynState) -> Option<bool> {
+    Dfa::decide(self, state.get())
+  }
+}
+
+pub struct DynDfa<'a, X>(Box<dyn ErasedDfa<X> + 'a>);
+
+impl<'a, X> DynDfa<'a, X> {
+  pub fn new<D: Dfa<X> + 'a>(dfa: D) -> Self
+  where
+    D::State: Any + Clone + Ord,
+  {
+    DynDfa(Box::new(dfa))
+  }
+}
+
+impl<X> Dfa<X> for DynDfa<'_, X> {
+  type State = DynState;
+  fn initial(&self) -> Self::State {
+    self.0.initial()
+  }
+  fn next(&self, state: Self::State, char: X) -> Option<Self::State> {
+    self.0.next(state, char)
+  }
+  fn accept(&self, state: &Self::State) -> bool {
+    self.0.accept(state)
+  }
+  fn decide(&self, state: &Self::State) -> Option<bool> {
+    self.0.decide(state)
+  }
+}
+
+trait ErasedThreeFA<X> {
+  fn initial(&self) -> DynState;
+  fn step_pre(&self, state: DynState, char: X) -> Option<DynState>;
+  fn step_active(&self, state: DynState, char: X) -> Option<DynState>;
+  fn step_post(&self, state: DynState, char: X) -> Option<DynState>;
+  fn accept(&self, state: &DynState) -> bool;
+  fn decide(&self, state: &DynState) -> Option<bool>;
+  fn enter(&self, state: DynState) -> Option<DynState>;
+  fn exit(&self, state: DynState) -> Option<DynState>;
+}
+
+impl<X, A: ThreeFA<X>> ErasedThreeFA<X> for A
+where
+  A::Pre: Any + Clone + Ord,
+  A::Active: Any + Clone + Ord,
+  A::Post: Any + Clone + Ord,
+{
+  fn initial(&self) -> DynState {
+    DynState::new(ThreeFA::initial(self))
+  }
+  fn step_pre(&self, state: DynState, char: X) -> Option<DynState> {
+    ThreeFA::step_pre(self, state.take(), char).map(DynState::new)
+  }
+  fn step_active(&self, state: DynState, char: X) -> Option<DynState> {
+    ThreeFA::step_active(self, state.take(), char).map(DynState::new)
+  }
+  fn step_post(&self, state: DynState, char: X) -> Option<DynState> {
+    ThreeFA::step_post(self, state.take(), char).map(DynState::new)
+  }
+  fn accept(&self, state: &DynState) -> bool {
+    ThreeFA::accept(self, state.get())
+  }
+  fn decide(&self, state: &DynState) -> Option<bool> {
+    ThreeFA::decide(self, state.get())
+  }
+  fn enter(&self, state: DynState) -> Option<DynState> {
+    ThreeFA::enter(self, state.take()).map(DynState::new)
+  }
+  fn exit(&self, state: DynState) -> Option<DynState> {
+    ThreeFA::exit(self, state.take()).map(DynState::new)
+  }
+}
+
+pub struct DynThreeFA<'a, X>(Box<dyn ErasedThreeFA<X> + 'a>);
+
+impl<'a, X> DynThreeFA<'a, X> {
+  pub fn new<A: ThreeFA<X> + 'a>(threefa: A) -> Self
+  where
+    A::Pre: Any + Clone + Ord,
+    A::Active: Any + Clone + Ord,
+    A::Post: Any + Clone + Ord,
+  {
+    DynThreeFA(Box::new(threefa))
+  }
+}
+
+impl<X> ThreeFA<X> for DynThreeFA<'_, X> {
+  type Pre = DynState;
+  type Active = DynState;
+  type Post = DynState;
+  fn initial(&self) -> Self::Pre {
+    self.0.initial()
+  }
+  fn step_pre(&self, state: Self::Pre, char: X) -> Option<Self::Pre> {
+    self.0.step_pre(state, char)
+  }
+  fn step_active(&self, state: Self::Active, char: X) -> Option<Self::Active> {
+    self.0.step_active(state, char)
+  }
+  fn step_post(&self, state: Self::Post, char: X) -> Option<Self::Post> {
+    self.0.step_post(state, char)
+  }
+  fn accept(&self, state: &Self::Post) -> bool {
+    self.0.accept(state)
+  }
+  fn decide(&self, state: &Self::Post) -> Option<bool> {
+    self.0.decide(state)
+  }
+  fn enter(&self, state: Self::Pre) -> Option<Self::Active> {
+    self.0.enter(state)
+  }
+  fn exit(&self, state: Self::Active) -> Option<Self::Post> {
+    self.0.exit(state)
+  }
+}
@@ -16,12 +16,24 @@ pub fn is_empty<X: Clone, D: Dfa<X>>(
   dfa: D,
   alphabet: impl Clone + IntoIterator<Item = X>,
 ) -> Result<(), Vec<X>>
+where
+  D::State: Clone + Ord,
+{
+  let initial = dfa.initial();
+  is_empty_from(dfa, initial, alphabet)
+}
+
+pub fn is_empty_from<X: Clone, D: Dfa<X>>(
+  dfa: D,
+  state: D::State,
+  alphabet: impl Clone + IntoIterator<Item = X>,
+) -> Result<(), Vec<X>>
 where
   D::State: Clone + Ord,
 {
   let mut visited = BTreeSet::new();
   let mut next = BTreeMap::new();
-  next.insert(dfa.initial(), vec![]);
+  next.insert(state, vec![]);
   while !next.is_empty() {
     for (state, msg) in std::mem::take(&mut next) {
       if dfa.accept(&state) {
@@ -38,3 +50,21 @@ where
   }
   Ok(())
 }
+
+pub fn equal_bytes<A: Dfa<u8> + Classify, B: Dfa<u8> + Classify>(a: A, b: B) -> Result<(), Vec<u8>>
+where
+  A::State: Clone + Ord,
+  B::State: Clone + Ord,
+{
+  let mut classes = byte_classes(&a);
+  b.classify(&mut classes);
+  equal(a, b, classes.representatives())
+}
+
+pub fn is_empty_bytes<D: Dfa<u8> + Classify>(dfa: D) -> Result<(), Vec<u8>>
+where
+  D::State: Clone + Ord,
+{
+  let alphabet = infer_alphabet(&dfa);
+  is_empty(dfa, alphabet)
+}
@@ -0,0 +1,105 @@
+use crate::*;
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+  Matched(Range<usize>),
+  Failed,
+  Unused,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+  pub path: Vec<usize>,
+  pub label: String,
+  pub status: Status,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+  pub span: Option<Range<usize>>,
+  pub steps: Vec<Step>,
+}
+
+impl std::fmt::Display for Explanation {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    for step in &self.steps {
+      let status = match &step.status {
+        Status::Matched(x) => format!("{x:?}"),
+        Status::Failed => "failed".to_owned(),
+        Status::Unused => "-".to_owned(),
+      };
+      let indent = "  ".repeat(step.path.len());
+      writeln!(f, "{indent}{} {status}", step.label)?;
+    }
+    Ok(())
+  }
+}
+
+// Every subexpression is tracked like a capture group, so a match reports
+// the span each node took in the chosen derivation (the last iteration under
+// a repetition) and alternatives never taken stay unused. Without a match, the
+// longest prefix of the top-level concatenation that still matches somewhere
+// is explained instead and the part after it is marked as failed.
+pub fn explain(pattern: &Expr, input: &[u8]) -> Explanation {
+  let mut nodes = vec![];
+  each_node(pattern, &mut vec![], &mut |x, path| {
+    nodes.push((x as *const Expr, path.to_vec(), label(x)));
+  });
+  let tracked = nodes.iter().enumerate().map(|(i, x)| (x.0, i)).collect();
+  let tagger = Tagger::new(pattern, input, tracked, true);
+
+  let span = find(pattern, input);
+  let mut spans = vec![None; nodes.len()];
+  let mut failed = None;
+  match &span {
+    Some(span) => spans = tagger.tag(&[pattern], span.clone()),
+    None => {
+      let mut parts = vec![];
+      let mut rest = pattern;
+      while let Expr::Concat(a, b) = rest {
+        parts.push(&**a);
+        rest = b;
+      }
+      parts.push(rest);
+      for k in (0..parts.len()).rev() {
+        let prefix = Expr::concat_all(parts[..k].iter().map(|&x| x.clone()));
+        if let Some(span) = find(&prefix, input) {
+          spans = tagger.tag(&parts[..k], span);
+          failed = Some(parts[k] as *const Expr);
+          break;
+        }
+      }
+    }
+  }
+
+  let steps = nodes
+    .into_iter()
+    .zip(spans)
+    .map(|((ptr, path, label), span)| Step {
+      path,
+      label,
+      status: match span {
+        Some(x) => Status::Matched(x),
+        None if failed == Some(ptr) => Status::Failed,
+        None => Status::Unused,
+      },
+    })
+    .collect();
+  Explanation { span, steps }
+}
+
+fn label(expr: &Expr) -> String {
+  match expr {
+    Expr::Class(x) => class_label(*x),
+    Expr::Literal(x) => format!("{:?}", String::from_utf8_lossy(x)),
+    Expr::Repeat(_, min, Some(max)) => format!("Repeat{{{min},{max}}}"),
+    Expr::Repeat(_, min, None) => format!("Repeat{{{min},}}"),
+    Expr::Group(Some(name), _) => format!("Group<{name}>"),
+    _ => format!("{expr:?}")
+      .split(['(', ' '])
+      .next()
+      .unwrap()
+      .to_owned(),
+  }
+}
@@ -0,0 +1,498 @@
+use crate::*;
+use std::borrow::Borrow;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+  Empty,
+  Anything,
+  Dot,
+  Class(Class),
+  Utf8Class(Utf8Class),
+  Literal(Vec<u8>),
+  Not(Box<Expr>),
+  Or(Box<Expr>, Box<Expr>),
+  And(Box<Expr>, Box<Expr>),
+  Iff(Box<Expr>, Box<Expr>),
+  Concat(Box<Expr>, Box<Expr>),
+  Plus(Box<Expr>),
+  Repeat(Box<Expr>, usize, Option<usize>),
+  Start,
+  End,
+  LineStart(bool),
+  LineEnd(bool),
+  AtOffset(usize),
+  AtOffsetFromEnd(usize),
+  AtOffsetMod(usize, usize),
+  WordBoundary,
+  NotWordBoundary,
+  LookAhead(Box<Expr>),
+  LookBehind(Box<Expr>),
+  FromDfa(Box<Expr>),
+  ToDfa(Box<Expr>),
+  Group(Option<String>, Box<Expr>),
+}
+
+impl Expr {
+  pub fn concat_all(items: impl IntoIterator<Item = Expr>) -> Expr {
+    let items = items.into_iter().collect::<Vec<_>>();
+    items
+      .into_iter()
+      .rev()
+      .reduce(|b, a| Expr::Concat(Box::new(a), Box::new(b)))
+      .unwrap_or(Expr::Empty)
+  }
+
+  pub fn or_all(items: impl IntoIterator<Item = Expr>) -> Expr {
+    let items = items.into_iter().collect::<Vec<_>>();
+    items
+      .into_iter()
+      .rev()
+      .reduce(|b, a| Expr::Or(Box::new(a), Box::new(b)))
+      .unwrap_or(Expr::Not(Box::new(Expr::Anything)))
+  }
+}
+
+// The anchors, word boundaries and lookarounds have no meaning as a plain
+// Dfa; there they match whatever `^ x $` would match as a 3FA.
+macro_rules! with_dfa {
+  ($expr:expr, |$d:ident| $body:expr) => {
+    match $expr {
+      Expr::Empty => {
+        let $d = Empty;
+        $body
+      }
+      Expr::Anything => {
+        let $d = Anything;
+        $body
+      }
+      Expr::Dot => {
+        let $d = Dot;
+        $body
+      }
+      Expr::Class(x) => {
+        let $d = *x;
+        $body
+      }
+      Expr::Utf8Class(x) => {
+        let $d = x;
+        $body
+      }
+      Expr::Literal(x) => {
+        let $d = &x[..];
+        $body
+      }
+      Expr::Not(a) => {
+        let $d = Not(&**a);
+        $body
+      }
+      Expr::Or(a, b) => {
+        let $d = Or(&**a, &**b);
+        $body
+      }
+      Expr::And(a, b) => {
+        let $d = And(&**a, &**b);
+        $body
+      }
+      Expr::Iff(a, b) => {
+        let $d = Iff(&**a, &**b);
+        $body
+      }
+      Expr::Concat(a, b) => {
+        let $d = Concat(&**a, &**b);
+        $body
+      }
+      Expr::Plus(a) => {
+        let $d = Plus(&**a);
+        $body
+      }
+      Expr::Repeat(a, min, max) => {
+        let $d = Repeat {
+          inner: &**a,
+          min: *min,
+          max: *max,
+        };
+        $body
+      }
+      Expr::FromDfa(a) => {
+        let $d = FromDfa(&**a);
+        $body
+      }
+      Expr::ToDfa(a) => {
+        let $d = ToDfa(&**a);
+        $body
+      }
+      Expr::Group(_, a) => {
+        let $d = &**a;
+        $body
+      }
+      x @ (Expr::Start
+      | Expr::End
+      | Expr::LineStart(_)
+      | Expr::LineEnd(_)
+      | Expr::AtOffset(_)
+      | Expr::AtOffsetFromEnd(_)
+      | Expr::AtOffsetMod(..)
+      | Expr::WordBoundary
+      | Expr::NotWordBoundary
+      | Expr::LookAhead(_)
+      | Expr::LookBehind(_)) => {
+        let $d = ToDfa(Concat(Start, Concat(x, End)));
+        $body
+      }
+    }
+  };
+}
+
+macro_rules! with_threefa {
+  ($expr:expr, |$d:ident| $body:expr) => {
+    match $expr {
+      x @ (Expr::Empty
+      | Expr::Anything
+      | Expr::Dot
+      | Expr::Class(_)
+      | Expr::Utf8Class(_)
+      | Expr::Literal(_)
+      | Expr::Iff(..)
+      | Expr::ToDfa(_)) => {
+        let $d = FromDfa(x);
+        $body
+      }
+      Expr::Not(a) => {
+        let $d = Not(&**a);
+        $body
+      }
+      Expr::Or(a, b) => {
+        let $d = Or(&**a, &**b);
+        $body
+      }
+      Expr::And(a, b) => {
+        let $d = And(&**a, &**b);
+        $body
+      }
+      Expr::FromDfa(a) => {
+        let $d = FromDfa(&**a);
+        $body
+      }
+      Expr::Concat(a, b) => {
+        let $d = Concat(&**a, &**b);
+        $body
+      }
+      Expr::Plus(a) => {
+        let $d = Plus(&**a);
+        $body
+      }
+      Expr::Repeat(a, min, max) => {
+        let $d = Repeat {
+          inner: &**a,
+          min: *min,
+          max: *max,
+        };
+        $body
+      }
+      Expr::Start => {
+        let $d = Start;
+        $body
+      }
+      Expr::End => {
+        let $d = End;
+        $body
+      }
+      Expr::LineStart(crlf) => {
+        let $d = LineStart { crlf: *crlf };
+        $body
+      }
+      Expr::LineEnd(crlf) => {
+        let $d = LineEnd { crlf: *crlf };
+        $body
+      }
+      Expr::AtOffset(n) => {
+        let $d = AtOffset(*n);
+        $body
+      }
+      Expr::AtOffsetFromEnd(n) => {
+        let $d = AtOffsetFromEnd(*n);
+        $body
+      }
+      Expr::AtOffsetMod(n, k) => {
+        let $d = AtOffsetMod(*n, *k);
+        $body
+      }
+      Expr::WordBoundary => {
+        let $d = WordBoundary;
+        $body
+      }
+      Expr::NotWordBoundary => {
+        let $d = NotWordBoundary;
+        $body
+      }
+      Expr::LookAhead(a) => {
+        let $d = LookAhead(&**a);
+        $body
+      }
+      Expr::LookBehind(a) => {
+        let $d = LookBehind(&**a);
+        $body
+      }
+      Expr::Group(_, a) => {
+        let $d = &**a;
+        $body
+      }
+    }
+  };
+}
+
+impl<X: Clone + Borrow<u8>> Dfa<X> for Expr
+where
+  [u8]: Dfa<X, State = usize>,
+{
+  type State = Value;
+  fn initial(&self) -> Self::State {
+    with_dfa!(self, |d| as_dfa::initial::<X, _>(d))
+  }
+  fn next(&self, state: Self::State, char: X) -> Option<Self::State> {
+    with_dfa!(self, |d| as_dfa::next::<X, _>(d, state, char))
+  }
+  fn accept(&self, state: &Self::State) -> bool {
+    with_dfa!(self, |d| as_dfa::accept::<X, _>(d, state))
+  }
+  fn decide(&self, state: &Self::State) -> Option<bool> {
+    with_dfa!(self, |d| as_dfa::decide::<X, _>(d, state))
+  }
+}
+
+impl<X: Clone + Borrow<u8>> ThreeFA<X> for Expr
+where
+  [u8]: Dfa<X, State = usize>,
+{
+  type Pre = Value;
+  type Active = Value;
+  type Post = Value;
+  fn initial(&self) -> Self::Pre {
+    with_threefa!(self, |d| as_threefa::initial::<X, _>(d))
+  }
+  fn step_pre(&self, state: Self::Pre, char: X) -> Option<Self::Pre> {
+    with_threefa!(self, |d| as_threefa::step_pre::<X, _>(d, state, char))
+  }
+  fn step_active(&self, state: Self::Active, char: X) -> Option<Self::Active> {
+    with_threefa!(self, |d| as_threefa::step_active::<X, _>(d, state, char))
+  }
+  fn step_post(&self, state: Self::Post, char: X) -> Option<Self::Post> {
+    with_threefa!(self, |d| as_threefa::step_post::<X, _>(d, state, char))
+  }
+  fn accept(&self, state: &Self::Post) -> bool {
+    with_threefa!(self, |d| as_threefa::accept::<X, _>(d, state))
+  }
+  fn decide(&self, state: &Self::Post) -> Option<bool> {
+    with_threefa!(self, |d| as_threefa::decide::<X, _>(d, state))
+  }
+  fn enter(&self, state: Self::Pre) -> Option<Self::Active> {
+    with_threefa!(self, |d| as_threefa::enter::<X, _>(d, state))
+  }
+  fn exit(&self, state: Self::Active) -> Option<Self::Post> {
+    with_threefa!(self, |d| as_threefa::exit::<X, _>(d, state))
+  }
+}
+
+macro_rules! from_leaf {
+  ($($t:ty => $e:expr),* $(,)?) => {
+    $(
+      impl From<$t> for Expr {
+        fn from(_: $t) -> Self {
+          $e
+        }
+      }
+    )*
+  };
+}
+
+from_leaf! {
+  Empty => Expr::Empty,
+  Anything => Expr::Anything,
+  Dot => Expr::Dot,
+  Start => Expr::Start,
+  End => Expr::End,
+  WordBoundary => Expr::WordBoundary,
+  NotWordBoundary => Expr::NotWordBoundary,
+}
+
+impl From<LineStart> for Expr {
+  fn from(x: LineStart) -> Self {
+    Expr::LineStart(x.crlf)
+  }
+}
+
+impl From<LineEnd> for Expr {
+  fn from(x: LineEnd) -> Self {
+    Expr::LineEnd(x.crlf)
+  }
+}
+
+impl From<AtOffset> for Expr {
+  fn from(x: AtOffset) -> Self {
+    Expr::AtOffset(x.0)
+  }
+}
+
+impl From<AtOffsetFromEnd> for Expr {
+  fn from(x: AtOffsetFromEnd) -> Self {
+    Expr::AtOffsetFromEnd(x.0)
+  }
+}
+
+impl From<AtOffsetMod> for Expr {
+  fn from(x: AtOffsetMod) -> Self {
+    Expr::AtOffsetMod(x.0, x.1)
+  }
+}
+
+impl From<Class> for Expr {
+  fn from(x: Class) -> Self {
+    Expr::Class(x)
+  }
+}
+
+impl From<Utf8Class> for Expr {
+  fn from(x: Utf8Class) -> Self {
+    Expr::Utf8Class(x)
+  }
+}
+
+impl From<&[u8]> for Expr {
+  fn from(x: &[u8]) -> Self {
+    Expr::Literal(x.to_vec())
+  }
+}
+
+impl<const N: usize> From<&[u8; N]> for Expr {
+  fn from(x: &[u8; N]) -> Self {
+    Expr::Literal(x.to_vec())
+  }
+}
+
+macro_rules! from_unary {
+  ($($t:ident),*) => {
+    $(
+      impl<A: Into<Expr>> From<$t<A>> for Expr {
+        fn from(x: $t<A>) -> Self {
+          Expr::$t(Box::new(x.0.into()))
+        }
+      }
+    )*
+  };
+}
+
+from_unary!(Not, Plus, LookAhead, LookBehind, FromDfa, ToDfa);
+
+macro_rules! from_binary {
+  ($($t:ident),*) => {
+    $(
+      impl<A: Into<Expr>, B: Into<Expr>> From<$t<A, B>> for Expr {
+        fn from(x: $t<A, B>) -> Self {
+          Expr::$t(Box::new(x.0.into()), Box::new(x.1.into()))
+        }
+      }
+    )*
+  };
+}
+
+from_binary!(Or, And, Iff, Concat);
+
+impl<A: Into<Expr>> From<Group<A>> for Expr {
+  fn from(x: Group<A>) -> Self {
+    Expr::Group(x.0.map(String::from), Box::new(x.1.into()))
+  }
+}
+
+impl<A: Into<Expr>> From<Repeat<A>> for Expr {
+  fn from(x: Repeat<A>) -> Self {
+    Expr::Repeat(Box::new(x.inner.into()), x.min, x.max)
+  }
+}
+
+mod as_dfa {
+  use crate::*;
+
+  pub fn initial<X, D: Dfa<X>>(d: D) -> Value
+  where
+    D::State: Dynamic,
+  {
+    d.initial().into_value()
+  }
+  pub fn next<X, D: Dfa<X>>(d: D, state: Value, char: X) -> Option<Value>
+  where
+    D::State: Dynamic,
+  {
+    d.next(Dynamic::from_value(state), char)
+      .map(Dynamic::into_value)
+  }
+  pub fn accept<X, D: Dfa<X>>(d: D, state: &Value) -> bool
+  where
+    D::State: Dynamic,
+  {
+    d.accept(&Dynamic::from_value(state.clone()))
+  }
+  pub fn decide<X, D: Dfa<X>>(d: D, state: &Value) -> Option<bool>
+  where
+    D::State: Dynamic,
+  {
+    d.decide(&Dynamic::from_value(state.clone()))
+  }
+}
+
+mod as_threefa {
+  use crate::*;
+
+  pub fn initial<X, D: ThreeFA<X>>(d: D) -> Value
+  where
+    D::Pre: Dynamic,
+  {
+    d.initial().into_value()
+  }
+  pub fn step_pre<X, D: ThreeFA<X>>(d: D, state: Value, char: X) -> Option<Value>
+  where
+    D::Pre: Dynamic,
+  {
+    d.step_pre(Dynamic::from_value(state), char)
+      .map(Dynamic::into_value)
+  }
+  pub fn step_active<X, D: ThreeFA<X>>(d: D, state: Value, char: X) -> Option<Value>
+  where
+    D::Active: Dynamic,
+  {
+    d.step_active(Dynamic::from_value(state), char)
+      .map(Dynamic::into_value)
+  }
+  pub fn step_post<X, D: ThreeFA<X>>(d: D, state: Value, char: X) -> Option<Value>
+  where
+    D::Post: Dynamic,
+  {
+    d.step_post(Dynamic::from_value(state), char)
+      .map(Dynamic::into_value)
+  }
+  pub fn accept<X, D: ThreeFA<X>>(d: D, state: &Value) -> bool
+  where
+    D::Post: Dynamic,
+  {
+    d.accept(&Dynamic::from_value(state.clone()))
+  }
+  pub fn decide<X, D: ThreeFA<X>>(d: D, state: &Value) -> Option<bool>
+  where
+    D::Post: Dynamic,
+  {
+    d.decide(&Dynamic::from_value(state.clone()))
+  }
+  pub fn enter<X, D: ThreeFA<X>>(d: D, state: Value) -> Option<Value>
+  where
+    D::Pre: Dynamic,
+    D::Active: Dynamic,
+  {
+    d.enter(Dynamic::from_value(state)).map(Dynamic::into_value)
+  }
+  pub fn exit<X, D: ThreeFA<X>>(d: D, state: Value) -> Option<Value>
+  where
+    D::Active: Dynamic,
+    D::Post: Dynamic,
+  {
+    d.exit(Dynamic::from_value(state)).map(Dynamic::into_value)
+  }
+}
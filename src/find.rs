@@ -0,0 +1,396 @@
+use crate::*;
+use std::cmp::Reverse;
+use std::ops::Range;
+
+pub fn find<X: Clone, A: ThreeFA<X>>(
+  pattern: A,
+  haystack: impl IntoIterator<Item = X>,
+) -> Option<Range<usize>>
+where
+  A::Pre: Clone,
+  A::Active: Clone + Ord,
+  A::Post: Clone + Ord,
+{
+  find_at(pattern, haystack, 0)
+}
+
+pub fn find_at<X: Clone, A: ThreeFA<X>>(
+  pattern: A,
+  haystack: impl IntoIterator<Item = X>,
+  from: usize,
+) -> Option<Range<usize>>
+where
+  A::Pre: Clone,
+  A::Active: Clone + Ord,
+  A::Post: Clone + Ord,
+{
+  // A single forward scan: every active state remembers the leftmost start
+  // that reaches it and every post state the best span that reaches it, since
+  // states that coincide have the same future. The scan stops early once the
+  // best span of a post state that accepts whatever follows cannot be beaten
+  // by an undecided post state or by an active state that started no later.
+  let mut pre = Some(pattern.initial());
+  let mut active = BTreeMap::<A::Active, usize>::new();
+  let mut post = BTreeMap::<A::Post, Range<usize>>::new();
+  let mut haystack = haystack.into_iter();
+  let mut offset = 0;
+  loop {
+    if let Some(state) = pre
+      .clone()
+      .filter(|_| offset >= from)
+      .and_then(|x| pattern.enter(x))
+    {
+      active.entry(state).or_insert(offset);
+    }
+    for (state, &start) in &active {
+      if let Some(state) = pattern.exit(state.clone()) {
+        keep_best(&mut post, state, start..offset);
+      }
+    }
+    post.retain(|state, _| pattern.decide(state) != Some(false));
+    if pre.is_none() && active.is_empty() && post.is_empty() {
+      return None;
+    }
+    let rank = |x: &Range<usize>| (x.start, Reverse(x.end));
+    let decided = post
+      .iter()
+      .filter(|(state, _)| pattern.decide(state) == Some(true))
+      .map(|(_, span)| span)
+      .min_by_key(|x| rank(x));
+    if let Some(best) = decided {
+      if post.values().all(|x| rank(x) >= rank(best))
+        && active.values().all(|&start| start > best.start)
+      {
+        return Some(best.clone());
+      }
+    }
+    let Some(char) = haystack.next() else {
+      break;
+    };
+    pre = pre.and_then(|x| pattern.step_pre(x, char.clone()));
+    let mut next_active = BTreeMap::new();
+    for (state, start) in active {
+      if let Some(state) = pattern.step_active(state, char.clone()) {
+        next_active
+          .entry(state)
+          .and_modify(|x: &mut usize| *x = start.min(*x))
+          .or_insert(start);
+      }
+    }
+    active = next_active;
+    let mut next_post = BTreeMap::new();
+    for (state, span) in post {
+      if let Some(state) = pattern.step_post(state, char.clone()) {
+        keep_best(&mut next_post, state, span);
+      }
+    }
+    post = next_post;
+    offset += 1;
+  }
+  post
+    .into_iter()
+    .filter(|(state, _)| pattern.accept(state))
+    .map(|(_, span)| span)
+    .min_by_key(|x| (x.start, Reverse(x.end)))
+}
+
+pub fn find_dfa<X: Clone, D: Dfa<X>>(
+  dfa: D,
+  haystack: impl IntoIterator<Item = X>,
+) -> Option<Range<usize>>
+where
+  D::State: Clone + Ord,
+{
+  find(FromDfa(dfa), haystack)
+}
+
+fn keep_best<S: Ord>(post: &mut BTreeMap<S, Range<usize>>, state: S, span: Range<usize>) {
+  post
+    .entry(state)
+    .and_modify(|x| {
+      if (span.start, Reverse(span.end)) < (x.start, Reverse(x.end)) {
+        *x = span.clone();
+      }
+    })
+    .or_insert(span);
+}
+
+// The pre states and the post states that go on to accept are worked out
+// once for the whole haystack, so each match is found by scanning only from
+// the previous one until no earlier or longer match can appear, and
+// lookarounds still see the text before the previous match.
+pub fn find_iter<'h, X, A: ThreeFA<&'h X> + 'h>(
+  pattern: A,
+  haystack: &'h [X],
+) -> impl Iterator<Item = Range<usize>> + 'h
+where
+  A::Pre: Clone,
+  A::Active: Clone + Ord,
+  A::Post: Clone + Ord,
+{
+  let chars = haystack.iter().collect::<Vec<_>>();
+  let (pre, accepting) = phases(&pattern, &chars);
+  let mut from = 0;
+  let mut last_end = None;
+  std::iter::from_fn(move || {
+    while from <= haystack.len() {
+      let span = next_match(&pattern, &chars, &pre, &accepting, from)?;
+      if span.is_empty() && last_end == Some(span.end) {
+        from = span.end + 1;
+        continue;
+      }
+      from = span.end.max(span.start + 1);
+      last_end = Some(span.end);
+      return Some(span);
+    }
+    None
+  })
+}
+
+// The pre state at every offset, and at every offset the post states that
+// accept the rest of the haystack, found by a forward pass over every post
+// state that can occur and a backward pass deciding them. Post states that
+// `decide` already settles are not stepped any further.
+pub(crate) type Phases<A, X> = (
+  Vec<Option<<A as ThreeFA<X>>::Pre>>,
+  Vec<BTreeSet<<A as ThreeFA<X>>::Post>>,
+);
+
+pub(crate) fn phases<X: Clone, A: ThreeFA<X>>(pattern: &A, haystack: &[X]) -> Phases<A, X>
+where
+  A::Pre: Clone,
+  A::Active: Clone + Ord,
+  A::Post: Clone + Ord,
+{
+  let mut pre = vec![Some(pattern.initial())];
+  let mut active = BTreeSet::new();
+  let mut posts = vec![];
+  let mut post = BTreeSet::new();
+  for offset in 0..=haystack.len() {
+    active.extend(pre[offset].clone().and_then(|x| pattern.enter(x)));
+    post.extend(active.iter().filter_map(|x| pattern.exit(x.clone())));
+    post.retain(|x| pattern.decide(x) != Some(false));
+    posts.push(post.clone());
+    let Some(char) = haystack.get(offset) else {
+      break;
+    };
+    pre.push(
+      pre[offset]
+        .clone()
+        .and_then(|x| pattern.step_pre(x, char.clone())),
+    );
+    active = active
+      .into_iter()
+      .filter_map(|x| pattern.step_active(x, char.clone()))
+      .collect();
+    post = post
+      .into_iter()
+      .filter(|x| pattern.decide(x).is_none())
+      .filter_map(|x| pattern.step_post(x, char.clone()))
+      .collect();
+  }
+  let mut accepting = vec![BTreeSet::new(); posts.len()];
+  for (offset, post) in posts.into_iter().enumerate().rev() {
+    accepting[offset] = match haystack.get(offset) {
+      None => post.into_iter().filter(|x| pattern.accept(x)).collect(),
+      Some(char) => post
+        .into_iter()
+        .filter(|x| {
+          pattern.decide(x).unwrap_or_else(|| {
+            let next = pattern.step_post(x.clone(), char.clone());
+            next.is_some_and(|x| accepting[offset + 1].contains(&x))
+          })
+        })
+        .collect(),
+    };
+  }
+  (pre, accepting)
+}
+
+fn next_match<X: Clone, A: ThreeFA<X>>(
+  pattern: &A,
+  haystack: &[X],
+  pre: &[Option<A::Pre>],
+  accepting: &[BTreeSet<A::Post>],
+  from: usize,
+) -> Option<Range<usize>>
+where
+  A::Pre: Clone,
+  A::Active: Clone + Ord,
+  A::Post: Clone + Ord,
+{
+  let mut active = BTreeMap::<A::Active, usize>::new();
+  let mut best: Option<Range<usize>> = None;
+  for offset in from..=haystack.len() {
+    if best.is_none() {
+      if let Some(state) = pre[offset].clone().and_then(|x| pattern.enter(x)) {
+        active.entry(state).or_insert(offset);
+      }
+    }
+    for (state, &start) in &active {
+      let post = pattern.exit(state.clone());
+      if post.is_some_and(|x| accepting[offset].contains(&x))
+        && best.as_ref().is_none_or(|x| start <= x.start)
+      {
+        best = Some(start..offset);
+      }
+    }
+    if let Some(best) = &best {
+      active.retain(|_, start| *start <= best.start);
+      if active.is_empty() {
+        break;
+      }
+    }
+    let Some(char) = haystack.get(offset) else {
+      break;
+    };
+    let mut next_active = BTreeMap::new();
+    for (state, start) in active {
+      if let Some(state) = pattern.step_active(state, char.clone()) {
+        next_active
+          .entry(state)
+          .and_modify(|x: &mut usize| *x = start.min(*x))
+          .or_insert(start);
+      }
+    }
+    active = next_active;
+  }
+  best
+}
+
+pub fn find_iter_dfa<'h, X, D: Dfa<&'h X> + 'h>(
+  dfa: D,
+  haystack: &'h [X],
+) -> impl Iterator<Item = Range<usize>> + 'h
+where
+  D::State: Clone + Ord,
+{
+  find_iter(FromDfa(dfa), haystack)
+}
+
+pub fn replacen<'h, X: Clone, A: ThreeFA<&'h X> + 'h>(
+  pattern: A,
+  haystack: &'h [X],
+  limit: usize,
+  with: &[X],
+) -> Vec<X>
+where
+  A::Pre: Clone,
+  A::Active: Clone + Ord,
+  A::Post: Clone + Ord,
+{
+  let mut out = vec![];
+  let mut last = 0;
+  for span in find_iter(pattern, haystack).take(limit) {
+    out.extend_from_slice(&haystack[last..span.start]);
+    out.extend_from_slice(with);
+    last = span.end;
+  }
+  out.extend_from_slice(&haystack[last..]);
+  out
+}
+
+pub fn replace_all<'h, X: Clone, A: ThreeFA<&'h X> + 'h>(
+  pattern: A,
+  haystack: &'h [X],
+  with: &[X],
+) -> Vec<X>
+where
+  A::Pre: Clone,
+  A::Active: Clone + Ord,
+  A::Post: Clone + Ord,
+{
+  replacen(pattern, haystack, usize::MAX, with)
+}
+
+pub fn split<'h, X, A: ThreeFA<&'h X> + 'h>(pattern: A, haystack: &'h [X]) -> Vec<&'h [X]>
+where
+  A::Pre: Clone,
+  A::Active: Clone + Ord,
+  A::Post: Clone + Ord,
+{
+  let mut out = vec![];
+  let mut last = 0;
+  for span in find_iter(pattern, haystack) {
+    out.push(&haystack[last..span.start]);
+    last = span.end;
+  }
+  out.push(&haystack[last..]);
+  out
+}
+
+pub fn replacen_dfa<'h, X: Clone, D: Dfa<&'h X> + 'h>(
+  dfa: D,
+  haystack: &'h [X],
+  limit: usize,
+  with: &[X],
+) -> Vec<X>
+where
+  D::State: Clone + Ord,
+{
+  replacen(FromDfa(dfa), haystack, limit, with)
+}
+
+pub fn replace_all_dfa<'h, X: Clone, D: Dfa<&'h X> + 'h>(
+  dfa: D,
+  haystack: &'h [X],
+  with: &[X],
+) -> Vec<X>
+where
+  D::State: Clone + Ord,
+{
+  replace_all(FromDfa(dfa), haystack, with)
+}
+
+pub fn split_dfa<'h, X, D: Dfa<&'h X> + 'h>(dfa: D, haystack: &'h [X]) -> Vec<&'h [X]>
+where
+  D::State: Clone + Ord,
+{
+  split(FromDfa(dfa), haystack)
+}
+
+// Pairs are recorded only when an active state exits into a post state that
+// `phases` found accepting. Every start sits in exactly one active state and
+// the smaller set is merged into the larger, so carrying the starts along
+// costs little beyond the pairs reported.
+pub fn spans<X: Clone, A: ThreeFA<X>>(
+  pattern: A,
+  input: impl IntoIterator<Item = X>,
+) -> Vec<Range<usize>>
+where
+  A::Pre: Clone,
+  A::Active: Clone + Ord,
+  A::Post: Clone + Ord,
+{
+  let input = input.into_iter().collect::<Vec<_>>();
+  let (pre, accepting) = phases(&pattern, &input);
+  let mut active = BTreeMap::<A::Active, BTreeSet<usize>>::new();
+  let mut out = vec![];
+  for offset in 0..=input.len() {
+    if let Some(state) = pre[offset].clone().and_then(|x| pattern.enter(x)) {
+      active.entry(state).or_default().insert(offset);
+    }
+    for (state, starts) in &active {
+      let post = pattern.exit(state.clone());
+      if post.is_some_and(|x| accepting[offset].contains(&x)) {
+        out.extend(starts.iter().map(|&start| start..offset));
+      }
+    }
+    let Some(char) = input.get(offset) else {
+      break;
+    };
+    let mut next_active = BTreeMap::<_, BTreeSet<_>>::new();
+    for (state, mut starts) in active {
+      if let Some(state) = pattern.step_active(state, char.clone()) {
+        let into = next_active.entry(state).or_default();
+        if into.len() < starts.len() {
+          std::mem::swap(into, &mut starts);
+        }
+        into.extend(starts);
+      }
+    }
+    active = next_active;
+  }
+  out.sort_by_key(|x| (x.start, x.end));
+  out
+}
@@ -2,11 +2,13 @@ use crate::*;
 
 pub trait Finite {}
 
+impl Finite for u32 {}
 impl Finite for usize {}
 impl Finite for bool {}
 
 impl<T: Finite> Finite for Option<T> {}
 impl<T: Finite> Finite for BTreeSet<T> {}
+impl<T: Finite> Finite for Vec<T> {}
 
 impl Finite for () {}
 impl<T: Finite> Finite for (T,) {}
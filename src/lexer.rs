@@ -0,0 +1,174 @@
+use crate::*;
+use std::any::Any;
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<K> {
+  pub kind: Option<K>,
+  pub span: Range<usize>,
+}
+
+pub struct LexerBuilder<'a, K> {
+  kinds: Vec<K>,
+  rules: Vec<DynThreeFA<'a, u8>>,
+}
+
+impl<'a, K: Clone> LexerBuilder<'a, K> {
+  pub fn rule<A: ThreeFA<u8> + 'a>(mut self, kind: K, pattern: A) -> Self
+  where
+    A::Pre: Any + Clone + Ord,
+    A::Active: Any + Clone + Ord,
+    A::Post: Any + Clone + Ord,
+  {
+    self.kinds.push(kind);
+    self.rules.push(DynThreeFA::new(pattern));
+    self
+  }
+
+  pub fn rule_dfa<D: Dfa<u8> + 'a>(self, kind: K, dfa: D) -> Self
+  where
+    D::State: Any + Clone + Ord,
+  {
+    self.rule(kind, FromDfa(dfa))
+  }
+
+  pub fn build(self) -> Lexer<'a, K> {
+    Lexer {
+      kinds: self.kinds,
+      rules: self.rules,
+    }
+  }
+}
+
+pub struct Lexer<'a, K> {
+  kinds: Vec<K>,
+  rules: Vec<DynThreeFA<'a, u8>>,
+}
+
+impl<'a, K: Clone> Lexer<'a, K> {
+  pub fn builder() -> LexerBuilder<'a, K> {
+    LexerBuilder {
+      kinds: vec![],
+      rules: vec![],
+    }
+  }
+
+  // Each token is the longest span starting at the end of the previous one
+  // that some rule matches as a 3FA over the whole input, attributed to the
+  // first such rule, so lookarounds see the text around the token. The rules
+  // run together as one baked automaton for each combination of states they
+  // enter a token in, with every state tagged by the post states the rules
+  // exit to in rule order; which of those accept depends on the rest of the
+  // input. Bytes where no non-empty token starts are grouped into error
+  // tokens with no kind.
+  pub fn tokens(&self, input: &[u8]) -> Vec<Token<K>> {
+    let phases = self
+      .rules
+      .iter()
+      .map(|x| phases(x, input))
+      .collect::<Vec<_>>();
+    // Only the bytes of the input are ever stepped on.
+    let alphabet = input.iter().copied().collect::<BTreeSet<_>>();
+    let mut baked = BTreeMap::new();
+    let mut tokens = vec![];
+    let mut pos = 0;
+    while pos < input.len() {
+      let start = self
+        .rules
+        .iter()
+        .zip(&phases)
+        .map(|(rule, (pre, _))| pre[pos].clone().and_then(|x| rule.enter(x)))
+        .collect::<Vec<_>>();
+      let (dfa, tags) = baked.entry(start.clone()).or_insert_with(|| {
+        let product = Product {
+          rules: &self.rules,
+          start,
+        };
+        bake_tagged(&product, &alphabet, |x| product.exits(x))
+      });
+      match longest(dfa, tags, &phases, input, pos) {
+        Some((end, rule)) => {
+          tokens.push(Token {
+            kind: Some(self.kinds[rule].clone()),
+            span: pos..end,
+          });
+          pos = end;
+        }
+        None => {
+          match tokens.last_mut() {
+            Some(Token { kind: None, span }) => span.end += 1,
+            _ => tokens.push(Token {
+              kind: None,
+              span: pos..pos + 1,
+            }),
+          }
+          pos += 1;
+        }
+      }
+    }
+    tokens
+  }
+}
+
+// The rules stepped side by side from the states they entered a token in.
+struct Product<'r, 'a> {
+  rules: &'r [DynThreeFA<'a, u8>],
+  start: Vec<Option<DynState>>,
+}
+
+impl Product<'_, '_> {
+  fn exits(&self, state: &[Option<DynState>]) -> Vec<(usize, DynState)> {
+    self
+      .rules
+      .iter()
+      .zip(state)
+      .enumerate()
+      .filter_map(|(i, (rule, x))| Some((i, rule.exit(x.clone()?)?)))
+      .collect()
+  }
+}
+
+impl Dfa<u8> for Product<'_, '_> {
+  type State = Vec<Option<DynState>>;
+  fn initial(&self) -> Self::State {
+    self.start.clone()
+  }
+  fn next(&self, state: Self::State, char: u8) -> Option<Self::State> {
+    let next = self
+      .rules
+      .iter()
+      .zip(state)
+      .map(|(rule, x)| rule.step_active(x?, char))
+      .collect::<Vec<_>>();
+    next.iter().any(Option::is_some).then_some(next)
+  }
+  fn accept(&self, state: &Self::State) -> bool {
+    !self.exits(state).is_empty()
+  }
+}
+
+// The end of the longest non-empty token starting at `pos` and the first
+// rule whose post state at that end accepts the rest of the input.
+fn longest(
+  dfa: &BakedDfa<u8>,
+  tags: &[Vec<(usize, DynState)>],
+  phases: &[Phases<DynThreeFA<u8>, u8>],
+  input: &[u8],
+  pos: usize,
+) -> Option<(usize, usize)> {
+  let mut state = 0;
+  let mut longest = None;
+  for end in pos + 1..=input.len() {
+    let Some(next) = dfa.next(state, input[end - 1]) else {
+      break;
+    };
+    state = next;
+    let first = tags[state]
+      .iter()
+      .find(|(rule, post)| phases[*rule].1[end].contains(post));
+    if let Some(&(rule, _)) = first {
+      longest = Some((end, rule));
+    }
+  }
+  longest
+}
@@ -1,22 +1,56 @@
 use std::collections::{BTreeMap, BTreeSet};
 
 mod bake_dfa;
+mod byte_classes;
+mod captures;
+mod check;
+mod decide;
+mod dense_dfa;
 mod dfa;
+mod dfa_set;
+mod dyn_dfa;
 mod equal;
+mod explain;
+mod expr;
+mod find;
 mod finite;
+mod lexer;
+mod matcher;
 mod matches;
+mod minimize;
+mod parse;
 mod regex;
 mod threefa;
 mod to_regex;
+mod unicode_tables;
+mod value;
+mod viable;
 
 use bake_dfa::*;
+use byte_classes::*;
+use captures::*;
+use check::*;
+use decide::*;
+use dense_dfa::*;
 use dfa::*;
+use dfa_set::*;
+use dyn_dfa::*;
 use equal::*;
+use explain::*;
+use expr::*;
+use find::*;
 use finite::*;
+use lexer::*;
+use matcher::*;
 use matches::*;
+use minimize::*;
+use parse::*;
 use regex::*;
 use threefa::*;
 use to_regex::*;
+use unicode_tables::*;
+use value::*;
+use viable::*;
 
 fn main() {
   #[rustfmt::skip]
@@ -65,4 +99,638 @@ fn main() {
   dbg!(bake_dfa(y, alphabet));
 
   assert_eq!(equal(y, bake_dfa(y, alphabet), alphabet), Ok(()));
+
+  assert_eq!(equal(And(y, Anything), y, alphabet), Ok(()));
+
+  let z = parse("^(?=a*b)aaa").unwrap();
+  assert_eq!(equal(y, ToDfa(&z), alphabet), Ok(()));
+  assert_eq!(equal(x, &parse("a*b").unwrap(), alphabet), Ok(()));
+
+  assert_eq!(
+    equal(
+      regex![ ((?<= ab) ... (?! b))+ $ ],
+      ToDfa(&parse(r"((?<=ab)...(?!b))+$").unwrap()),
+      alphabet
+    ),
+    Ok(())
+  );
+
+  let words: [&[u8]; 3] = [b"ab", b"ax", b"b"];
+  let w = Expr::or_all(words.into_iter().map(Expr::from));
+  assert_eq!(equal(&w, dfa![ab | ax | b], alphabet), Ok(()));
+
+  let patterns: Vec<Expr> = vec![x.into(), y.into()];
+  assert_eq!(equal(&patterns[0], x, alphabet), Ok(()));
+  assert_eq!(equal(&patterns[1], y, alphabet), Ok(()));
+
+  let patterns: Vec<DynDfa<&u8>> = vec![
+    DynDfa::new(x),
+    DynDfa::new(y),
+    DynDfa::new(ToDfa(DynThreeFA::new(_regex![ (?= a* b) aaa ]))),
+    DynDfa::new(bake_dfa(x, alphabet)),
+  ];
+  assert_eq!(
+    patterns
+      .iter()
+      .map(|p| matches(p, b"aaab"))
+      .collect::<Vec<_>>(),
+    [true, true, true, true]
+  );
+  assert_eq!(equal(&patterns[0], &patterns[3], alphabet), Ok(()));
+  assert!(equal(&patterns[1], &patterns[2], alphabet).is_err());
+  assert_eq!(to_regex(&patterns[0], alphabet), to_regex(x, alphabet));
+
+  let hex = dfa![ x ["0-9a-fA-F"]+ ];
+  assert!(matches(hex, b"x1F"));
+  assert!(!matches(hex, b"x"));
+  assert!(!matches(hex, b"xg"));
+  assert_eq!(
+    equal(hex, &parse(r"x[\da-fA-F]+").unwrap(), b"x1Fg"),
+    Ok(())
+  );
+
+  let not_ab = dfa![ ["^ab"]+ ];
+  assert!(matches(not_ab, b"x\xff"));
+  assert!(!matches(not_ab, b"xa"));
+  assert_eq!(equal(not_ab, &parse(r"[^a-b]+").unwrap(), alphabet), Ok(()));
+  let digits = dfa![ [r"\d"]+ ];
+  assert!(matches(digits, b"0123"));
+  assert!(!matches(digits, b"d"));
+  let a_or_space = dfa![ ["a b"]+ ];
+  assert!(matches(a_or_space, b"a a"));
+  assert!(matches(dfa![ ["0-9e"]+ ], b"1e9"));
+  assert_eq!(
+    equal(
+      &parse(r"\w\s\x41").unwrap(),
+      &parse("[_a-zA-Z0-9][ \t-\r]A").unwrap(),
+      b"_a0 \tA"
+    ),
+    Ok(())
+  );
+
+  let r = dfa![ a{2,3} b ];
+  assert!(!matches(r, b"ab"));
+  assert!(matches(r, b"aab"));
+  assert!(matches(r, b"aaab"));
+  assert!(!matches(r, b"aaaab"));
+  assert_eq!(equal(dfa![ .{3} ], dfa![ ... ], alphabet), Ok(()));
+  assert_eq!(equal(dfa![ (a?){3} ], dfa![ a{0,3} ], alphabet), Ok(()));
+  assert_eq!(
+    equal(dfa![ (ab){2,} ], dfa![ (ab) (ab)+ ], alphabet),
+    Ok(())
+  );
+  assert_eq!(
+    equal(regex![ (a?){3} b ], regex![ a{0,3} b ], alphabet),
+    Ok(())
+  );
+  assert_eq!(equal(r, &parse("a{2,3}b").unwrap(), alphabet), Ok(()));
+  assert_eq!(
+    equal(
+      ToDfa(&parse("((?<=ab)...(?!b)){1,}$").unwrap()),
+      regex![ ((?<= ab) ... (?! b))+ $ ],
+      alphabet
+    ),
+    Ok(())
+  );
+  assert_eq!(
+    equal(
+      ToDfa(&parse("^ab((?<=ab)...(?!b)){2}$").unwrap()),
+      ToDfa(&parse("^ab((?<=ab)...(?!b))((?<=ab)...(?!b))$").unwrap()),
+      alphabet
+    ),
+    Ok(())
+  );
+  assert_eq!(find(&parse("(^a){0,1}b").unwrap(), b"xb"), Some(1..2));
+  assert_eq!(find(&parse("(^a){0,}b").unwrap(), b"xb"), Some(1..2));
+  assert_eq!(
+    equal(
+      ToDfa(&parse("((?<=b)a){0,2}b").unwrap()),
+      ToDfa(&parse("((?<=b)a)?((?<=b)a)?b").unwrap()),
+      alphabet
+    ),
+    Ok(())
+  );
+
+  let baked = bake_dfa(y, alphabet);
+  let minimal = minimize(&baked, alphabet);
+  assert!(minimal.0.len() < baked.0.len());
+  assert_eq!(equal(y, &minimal, alphabet), Ok(()));
+  assert_eq!(
+    minimal,
+    minimize(&bake_dfa(dfa![ aaa a* b .* ], alphabet), alphabet)
+  );
+  #[rustfmt::skip]
+  let ends_b = dfa![ (a|b)* b ];
+  assert_eq!(
+    minimize(&bake_dfa(ends_b, alphabet), alphabet),
+    minimize(&bake_dfa(dfa![ (a* b)+ ], alphabet), alphabet)
+  );
+
+  let bytes = (0..=u8::MAX).collect::<Vec<_>>();
+  let dense = dense_dfa(&minimize(&bake_dfa(y, &bytes), &bytes));
+  assert_eq!(dense.stride, 3);
+  assert!(matches(&dense, b"aaaabab"));
+  assert!(!matches(&dense, b"aabaaab"));
+  assert!(matches(&dense, b"aaab\xff"));
+  assert_eq!(equal(y, &dense, alphabet), Ok(()));
+  let mut distinct = vec![(false, (0..=u8::MAX).map(|x| (x, x as usize + 1)).collect())];
+  distinct.extend((0..=u8::MAX).map(|_| (true, BTreeMap::new())));
+  assert_eq!(dense_dfa(&BakedDfa(distinct)).stride, 256);
+
+  let classes = byte_classes(&y);
+  assert_eq!(classes.representatives(), vec![0, b'a', b'b']);
+  assert_eq!(
+    classes.members(b'x'),
+    Class::EMPTY.with(b'a').with(b'b').negate()
+  );
+  assert_eq!(bake_classes(y, &classes), bake_dfa(y, &bytes));
+  assert_eq!(equal(y, &dense, classes.representatives()), Ok(()));
+  assert_eq!(
+    to_regex_classes(dfa![ a .* ], &byte_classes(&Concat(b"a", Dot))),
+    "(()(a))(|([^a]|a)([^a]|a)*())"
+  );
+
+  assert_eq!(infer_alphabet(&dfa![ a . ]), vec![0, b'a']);
+  assert_eq!(equal(dfa![ .* ], dfa![ (a|b)* ], b"ab"), Ok(()));
+  assert_eq!(equal_bytes(dfa![ .* ], dfa![ (a|b)* ]), Err(vec![0]));
+  assert_eq!(equal_bytes(y, &dense), Ok(()));
+  assert_eq!(is_empty_bytes(And(b"a", b"b")), Ok(()));
+  assert_eq!(bake_bytes(y), bake_dfa(y, &bytes));
+  assert_eq!(
+    to_regex_bytes(dfa![ a .* ]),
+    "(()(a))(|([^a]|a)([^a]|a)*())"
+  );
+
+  #[rustfmt::skip]
+  let (plus_b, a_or_ab) = (regex![ a+ b ], regex![ a | ab ]);
+  assert_eq!(find(plus_b.0, b"xaab ab"), Some(1..4));
+  assert_eq!(find(a_or_ab.0, b"xab"), Some(1..3));
+  assert_eq!(find(regex![ (?<= x) a+ ].0, b"aaxaa"), Some(3..5));
+  assert_eq!(find(regex![ a+ (?= b) ].0, b"aaxaab"), Some(3..5));
+  assert_eq!(find(regex![ a* ].0, b"bbaa"), Some(0..0));
+  assert_eq!(find(regex![ab].0, b"aabx"), Some(1..3));
+  assert_eq!(find(regex![ab].0, b"aaxb"), None);
+  let read = std::cell::Cell::new(0);
+  let long = b"ab".iter().chain(&[b'x'; 300_000]);
+  let counted = long.inspect(|_| read.set(read.get() + 1));
+  assert_eq!(find(regex![ab].0, counted), Some(0..2));
+  assert_eq!(read.get(), 3);
+  assert_eq!(find(y.0, b"aaaab"), Some(0..3));
+  assert_eq!(find(&parse("a(?=b)").unwrap(), b"aaab"), Some(2..3));
+  assert_eq!(find_dfa(x, b"xaabab"), Some(1..4));
+  assert_eq!(find_dfa(&dense, b"xaaab"), Some(1..5));
+
+  #[rustfmt::skip]
+  let star_b = regex![ a* b ];
+  let haystack = b"xaab ab b";
+  assert_eq!(
+    find_iter(star_b.0, haystack).collect::<Vec<_>>(),
+    vec![1..4, 5..7, 8..9]
+  );
+  assert_eq!(
+    find_iter(regex![ a* ].0, b"baab").collect::<Vec<_>>(),
+    vec![0..0, 1..3, 4..4]
+  );
+  assert_eq!(
+    find_iter(regex![ (?<= a) b ].0, b"abab").collect::<Vec<_>>(),
+    vec![1..2, 3..4]
+  );
+  assert_eq!(replace_all(regex![a+].0, haystack, b"A"), b"xAb Ab b");
+  assert_eq!(replacen(star_b.0, haystack, 2, b"_"), b"x_ _ b");
+  assert_eq!(replace_all(FromDfa(dfa![b]), haystack, b""), b"xaa a ");
+  assert_eq!(
+    split(regex![b" "].0, haystack),
+    vec![&b"xaab"[..], b"ab", b"b"]
+  );
+  assert_eq!(split(regex![x].0, b"x"), vec![&b""[..], b""]);
+  assert_eq!(find_iter_dfa(dfa![b], haystack).count(), 3);
+  assert_eq!(replacen_dfa(dfa![a], haystack, 3, b""), b"xb b b");
+  assert_eq!(replace_all_dfa(b"ab", haystack, b"-"), b"xa- - b");
+  assert_eq!(split_dfa(b" ", haystack).len(), 3);
+  let long = b"ab ".repeat(5000);
+  assert_eq!(replace_all(regex![b].0, &long, b"B").len(), long.len());
+  assert_eq!(find_iter(regex![ (?<= b" ") a ].0, &long).count(), 4999);
+
+  assert_eq!(spans(regex![a+].0, b"aab"), vec![0..1, 0..2, 1..2]);
+  assert_eq!(spans(regex![ a* ].0, b"a"), vec![0..0, 0..1, 1..1]);
+  assert_eq!(
+    spans(regex![ (?<= a) .* (?= b) ].0, b"abab"),
+    vec![1..1, 1..3, 3..3]
+  );
+  assert_eq!(spans(y.0, b"aaab"), vec![0..3]);
+  let any = spans(&parse(".*").unwrap(), &[b'x'; 800]);
+  assert_eq!(any.len(), 801 * 802 / 2);
+
+  let c = captures(&parse("(a+)(b+)").unwrap(), b"xaabbb").unwrap();
+  assert_eq!(c.spans, vec![Some(1..6), Some(1..3), Some(3..6)]);
+  let c = captures(&parse("(?:(a)|(b))+").unwrap(), b"ab").unwrap();
+  assert_eq!(c.spans, vec![Some(0..2), Some(0..1), Some(1..2)]);
+  let c = captures(&parse(r"(\w)+").unwrap(), b"abc").unwrap();
+  assert_eq!(c.get(1), Some(2..3));
+  let c = captures(&parse("(a)?a*").unwrap(), b"aa").unwrap();
+  assert_eq!(c.get(1), Some(0..1));
+  let c = captures(&parse("(a+)?(a*)").unwrap(), b"aaa").unwrap();
+  assert_eq!((c.get(1), c.get(2)), (Some(0..3), Some(3..3)));
+  let c = captures(&parse("(?:x(a))*").unwrap(), b"xaxa").unwrap();
+  assert_eq!(c.get(1), Some(3..4));
+  let c = captures(&parse("(a)|(b)").unwrap(), b"b").unwrap();
+  assert_eq!((c.get(1), c.get(2)), (None, Some(0..1)));
+  let c = captures(&parse("(?<=ab)(...)(?!b)").unwrap(), b"xab123c").unwrap();
+  assert_eq!(c.spans, vec![Some(3..6), Some(3..6)]);
+  let c = captures(&parse("a(?=(b+))").unwrap(), b"xabb").unwrap();
+  assert_eq!(c.spans, vec![Some(1..2), Some(2..4)]);
+  let c = captures(&parse("(?<=(a+))b").unwrap(), b"aab").unwrap();
+  assert_eq!(c.spans, vec![Some(2..3), Some(0..2)]);
+  let c = captures(
+    &parse(r"(?<year>\d+)-(?<month>\d+)").unwrap(),
+    b"on 2024-05",
+  )
+  .unwrap();
+  assert_eq!((c.name("year"), c.name("month")), (Some(3..7), Some(8..10)));
+  let kv = Expr::from(regex![ (?<key> ["a-z"]+) b"=" (?:["0-9"]+) ].0);
+  let c = captures(&kv, b"; key=42;").unwrap();
+  assert_eq!(c.spans, vec![Some(2..8), Some(2..5)]);
+  assert_eq!(c.names, vec![None, Some("key".to_owned())]);
+  assert_eq!(captures(&kv, b"key"), None);
+  let many = captures(&parse("(a)+").unwrap(), &[b'a'; 20000]).unwrap();
+  assert_eq!(many.get(1), Some(19999..20000));
+  let first = captures(&parse("(x).*").unwrap(), &[b'x'; 800]).unwrap();
+  assert_eq!(first.spans, vec![Some(0..800), Some(0..1)]);
+
+  let mut m = Matcher::new(y);
+  m.feed(b"aa");
+  assert!(!m.is_match_so_far() && m.can_still_match());
+  m.feed(b"ab");
+  let snapshot = m.clone();
+  assert!(m.is_match_so_far());
+  m.feed(b"xx");
+  assert!(m.finish());
+  let mut m = snapshot;
+  m.feed(b"\xff");
+  assert!(m.is_match_so_far());
+  let mut m = Matcher::new(regex![ (?<= ab) c ]);
+  for chunk in [&b"xa"[..], b"b", b"cd"] {
+    m.feed(chunk);
+  }
+  assert!(m.finish());
+  let mut m = Matcher::new(x);
+  m.feed(b"ab");
+  assert!(m.is_match_so_far() && m.can_still_match());
+  m.feed(b"a");
+  assert!(!m.can_still_match() && !m.finish());
+
+  let read = std::cell::Cell::new(0);
+  let input = |prefix: &'static [u8]| {
+    read.set(0);
+    let rest = std::iter::repeat_n(b'x', 1000);
+    prefix
+      .iter()
+      .copied()
+      .chain(rest)
+      .inspect(|_| read.set(read.get() + 1))
+  };
+  assert!(matches(Not(b"ab"), input(b"b")));
+  assert_eq!(read.get(), 1);
+  assert!(!matches(Or(b"ab", Not(Anything)), input(b"b")));
+  assert_eq!(read.get(), 1);
+  let early = decided(y, infer_alphabet(&y));
+  assert!(early.outcomes.values().any(|&x| x));
+  assert!(matches(&early, input(b"aaab")));
+  assert_eq!(read.get(), 4);
+  assert!(!matches(&early, input(b"aab")));
+  assert_eq!(read.get(), 3);
+  assert!(matches(y, input(b"aaab")));
+  assert_eq!(read.get(), 4);
+  let mut m = Matcher::new(&early);
+  m.feed(b"b");
+  assert!(!m.can_still_match());
+  assert!(matches(regex![ab], input(b"xab")));
+  assert_eq!(read.get(), 3);
+  assert!(!matches(regex![ ^ab ], input(b"b")));
+  assert_eq!(read.get(), 1);
+
+  let mut set = DfaSet::new();
+  set.push(x);
+  set.push(y);
+  set.push(regex![ (?<= x) b ]);
+  set.push(dfa![ .* ]);
+  assert_eq!(set.matches(b"aaab"), [0, 1, 3].into());
+  assert_eq!(set.matches(b"xb"), [2, 3].into());
+  assert_eq!(set.matches(b"xx"), [3].into());
+  let baked = set.bake(alphabet);
+  for input in [&b"aaab"[..], b"xb", b"ab", b"aaabxb", b""] {
+    assert_eq!(baked.matches(input), set.matches(input));
+  }
+
+  #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+  enum Kind {
+    If,
+    Ident,
+    Number,
+    Space,
+  }
+  let lexer = Lexer::builder()
+    .rule_dfa(Kind::If, dfa![if])
+    .rule(Kind::Ident, _regex![ ["a-z_"]+ (?<= ["a-z"]) ])
+    .rule_dfa(Kind::Number, dfa![ ["0-9"]+ ])
+    .rule_dfa(Kind::Space, dfa![ b" "+ ])
+    .build();
+  let tokens = lexer
+    .tokens(b"if iffy 42 ?! x_ _y")
+    .into_iter()
+    .map(|x| (x.kind, x.span))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    tokens,
+    vec![
+      (Some(Kind::If), 0..2),
+      (Some(Kind::Space), 2..3),
+      (Some(Kind::Ident), 3..7),
+      (Some(Kind::Space), 7..8),
+      (Some(Kind::Number), 8..10),
+      (Some(Kind::Space), 10..11),
+      (None, 11..13),
+      (Some(Kind::Space), 13..14),
+      (Some(Kind::Ident), 14..15),
+      (None, 15..16),
+      (Some(Kind::Space), 16..17),
+      (Some(Kind::Ident), 17..19),
+    ]
+  );
+  let lexer = Lexer::builder()
+    .rule(1, _regex![ (?<= b"=") ["0-9"]+ ])
+    .rule(2, _regex![ ["a-z"]+ (?= b"=") ])
+    .rule(3, regex![ab].0)
+    .rule_dfa(4, b"=")
+    .build();
+  let tokens = lexer.tokens(b"x=12 12 xxab yy");
+  assert_eq!(
+    tokens
+      .into_iter()
+      .map(|x| (x.kind, x.span))
+      .collect::<Vec<_>>(),
+    vec![
+      (Some(2), 0..1),
+      (Some(4), 1..2),
+      (Some(1), 2..4),
+      (None, 4..10),
+      (Some(3), 10..12),
+      (None, 12..15),
+    ]
+  );
+
+  assert!(viable_prefix(x, b"aa", alphabet));
+  assert!(!viable_prefix(x, b"aba", alphabet));
+  assert!(viable_prefix(y, b"aaa", alphabet));
+  assert!(!viable_prefix(y, b"ab", alphabet));
+  let state = run(y, b"aa").unwrap();
+  assert_eq!(allowed_next(y, state.clone(), alphabet), vec![&b'a']);
+  assert_eq!(
+    completions(y, state, alphabet).take(4).collect::<Vec<_>>(),
+    vec![
+      vec![&b'a', &b'b'],
+      vec![&b'a', &b'a', &b'b'],
+      vec![&b'a', &b'b', &b'a'],
+      vec![&b'a', &b'b', &b'b']
+    ]
+  );
+  let ab_b = dfa![ab | b];
+  assert_eq!(
+    completions(ab_b, run(ab_b, b"").unwrap(), alphabet).collect::<Vec<_>>(),
+    vec![vec![&b'b'], vec![&b'a', &b'b']]
+  );
+  let abc = ToDfa(parse(".*abc").unwrap());
+  assert_eq!(
+    completions(&abc, run(&abc, b"").unwrap(), 0..=u8::MAX).next(),
+    Some(b"abc".to_vec())
+  );
+
+  let key = dfa![ ["a-z"]+ b"=" ["0-9"]+ ];
+  assert_eq!(check(key, b"port=80"), Ok(()));
+  let error = check(key, b"port:80").unwrap_err();
+  assert_eq!((error.offset, error.found), (4, Some(b':')));
+  assert_eq!(error.continuation, Some(b"=0".to_vec()));
+  assert_eq!(
+    error.to_string(),
+    "expected `[=a-z]` but found `:` at offset 4"
+  );
+  let error = check(key, b"port=").unwrap_err();
+  assert_eq!((error.offset, error.found), (5, None));
+  assert_eq!(error.expected, Class::DIGIT);
+  assert_eq!(error.continuation, Some(b"0".to_vec()));
+  let error = check(y, b"aab").unwrap_err();
+  assert_eq!((error.offset, error.found), (2, Some(b'b')));
+  assert_eq!(error.to_string(), "expected `a` but found `b` at offset 2");
+  let error = check(And(b"a", b"b"), b"").unwrap_err();
+  assert_eq!(error.to_string(), "unexpected end of input at offset 0");
+  assert_eq!(error.continuation, None);
+  let error = check(And(b"a", b"b"), b"xyz").unwrap_err();
+  assert_eq!(error.to_string(), "unexpected `x` at offset 0");
+  let error = check(ToDfa(&parse("ab").unwrap()), b"").unwrap_err();
+  assert_eq!(error.continuation, Some(b"ab".to_vec()));
+  let error = check(parse(".*abc").unwrap(), b"").unwrap_err();
+  assert_eq!(error.continuation, Some(b"abc".to_vec()));
+  let error = check(ToDfa(&parse("(?<=bx)").unwrap()), b"").unwrap_err();
+  assert_eq!(error.continuation, Some(b"bx".to_vec()));
+
+  let pattern = parse("(?<=x)(a|b)+(?=c)").unwrap();
+  let explanation = explain(&pattern, b"xabbc");
+  assert_eq!(explanation.span, Some(1..4));
+  assert_eq!(
+    explanation.to_string(),
+    [
+      "Concat 1..4",
+      "  LookBehind 1..1",
+      "    \"x\" 0..1",
+      "  Concat 1..4",
+      "    Plus 1..4",
+      "      Group 3..4",
+      "        Or 3..4",
+      "          \"a\" -",
+      "          \"b\" 3..4",
+      "    LookAhead 4..4",
+      "      \"c\" 4..5",
+      "",
+    ]
+    .join("\n")
+  );
+  let explanation = explain(&parse("(a|b)+").unwrap(), b"ab");
+  assert_eq!(
+    explanation.to_string(),
+    [
+      "Plus 0..2",
+      "  Group 1..2",
+      "    Or 1..2",
+      "      \"a\" -",
+      "      \"b\" 1..2",
+      "",
+    ]
+    .join("\n")
+  );
+  let explanation = explain(&pattern, b"xabbd");
+  assert_eq!(explanation.span, None);
+  let status = |path: &[usize]| {
+    let step = explanation.steps.iter().find(|x| x.path == path);
+    step.unwrap().status.clone()
+  };
+  assert_eq!(status(&[0]), Status::Matched(1..1));
+  assert_eq!(status(&[1, 0]), Status::Matched(1..4));
+  assert_eq!(status(&[1, 1]), Status::Failed);
+  let explanation = explain(&parse("(a)+").unwrap(), &[b'a'; 20000]);
+  assert_eq!(explanation.span, Some(0..20000));
+  assert_eq!(explanation.steps[1].status, Status::Matched(19999..20000));
+  let explanation = explain(&parse("(a)+b").unwrap(), &[b'a'; 2000]);
+  assert_eq!(explanation.span, None);
+  assert_eq!(explanation.steps[1].status, Status::Matched(0..2000));
+  assert_eq!(explanation.steps[4].status, Status::Failed);
+
+  let word = parse(r"\bab\b").unwrap();
+  assert_eq!(find(&word, b"cab ab"), Some(4..6));
+  assert_eq!(find(&word, b"ab"), Some(0..2));
+  assert_eq!(find(&word, b"abc cab"), None);
+  let inside = parse(r"\Bb\B").unwrap();
+  assert_eq!(find(&inside, b"b abc"), Some(3..4));
+  assert!(matches(ToDfa(&word), b"x ab."));
+  assert!(!matches(ToDfa(&word), b"xab."));
+
+  let line = parse(r"(?m)^b+$").unwrap();
+  assert_eq!(find(&line, b"a\nbb\nc"), Some(2..4));
+  assert_eq!(find(&line, b"a\nbb\r\nc"), None);
+  let crlf = parse(r"(?mR)^b+$").unwrap();
+  assert_eq!(find(&crlf, b"a\r\nbb\r\nc"), Some(3..5));
+  assert_eq!(find(&parse(r"(?m:^b)|\Ab").unwrap(), b"ab\nb"), Some(3..4));
+  assert_eq!(find(&parse(r"(?m)\Ab|b\z").unwrap(), b"b\nb"), Some(0..1));
+  assert_eq!(find(&parse(r"(?m)b\z").unwrap(), b"b\nb"), Some(2..3));
+  assert_eq!(find(&parse(r"(?m:^)b$").unwrap(), b"b\nb"), Some(2..3));
+  #[rustfmt::skip]
+  let lines = regex![ (?m) ^ b $ ].0;
+  assert_eq!(find(&lines, b"a\nb\nc"), Some(2..3));
+  #[rustfmt::skip]
+  let crlf_lines = regex![ (?mR) b $ ].0;
+  assert_eq!(find(&crlf_lines, b"ab\r\n"), Some(1..2));
+
+  let field = Concat(
+    AtOffset(2),
+    Concat(FromDfa(b"ab"), LookAhead(AtOffsetFromEnd(1))),
+  );
+  assert_eq!(find(&field, b"abababx"), None);
+  assert_eq!(find(&field, b"ababx"), Some(2..4));
+  let checksum = Concat(AtOffsetFromEnd(2), FromDfa(Plus(Dot)));
+  assert_eq!(find(&checksum, b"record42"), Some(6..8));
+  assert!(matches(ToDfa(LookBehind(AtOffset(3))), b"abc"));
+  assert!(!matches(ToDfa(LookBehind(AtOffset(3))), b"ab"));
+  let aligned = parse("b").unwrap();
+  let aligned = Concat(AtOffsetMod(0, 2), aligned);
+  assert_eq!(find_iter(&aligned, b"bbbbb").count(), 3);
+  assert_eq!(find(AtOffsetMod(1, 0), b"ab"), Some(1..1));
+  assert_eq!(find(AtOffsetMod(3, 0), b"ab"), None);
+
+  let cafe = parse("(?u)caf.").unwrap();
+  assert!(matches(&cafe, "café".as_bytes()));
+  assert!(!matches(parse("caf.").unwrap(), "café".as_bytes()));
+  assert!(!matches(&cafe, b"caf\xe9"));
+  let greek = parse("(?u)[α-ω]+").unwrap();
+  assert!(matches(&greek, "λογος".as_bytes()));
+  assert!(!matches(&greek, "logos".as_bytes()));
+  let other = parse(r"(?u)[^a\d]").unwrap();
+  assert!(matches(&other, "€".as_bytes()));
+  assert!(matches(&other, "🦀".as_bytes()));
+  assert!(!matches(&other, b"\xe2\x82"));
+  assert!(!matches(&other, b"\xed\xa0\x80"));
+  assert!(!matches(&other, b"\xc0\xaf"));
+  assert!(!matches(&other, b"7"));
+  let valid = bake_bytes(parse("(?u).*").unwrap());
+  assert!(matches(&valid, "naïve €".as_bytes()));
+  assert!(!matches(&valid, b"na\xefve"));
+  assert_eq!(
+    equal_bytes(
+      &parse(r"(?u)[\x00-\x7f]").unwrap(),
+      &parse(r"[\x00-\x7f]").unwrap()
+    ),
+    Ok(())
+  );
+  assert_eq!(
+    equal_bytes(&parse(r"(?u)\W").unwrap(), &parse(r"(?u)[^\w]").unwrap()),
+    Ok(())
+  );
+  let any = Utf8Class::any();
+  assert_eq!(any.negate().ranges, vec![]);
+  assert!(any.negate().negate().contains('\u{10FFFF}'));
+  assert_eq!(
+    find(&parse("(?u)é+").unwrap(), "aéé".as_bytes()),
+    Some(1..5)
+  );
+
+  assert_eq!(
+    parse(r"(?u)[\é]"),
+    Err(ParseError {
+      offset: 6,
+      message: "escaped non-ASCII character"
+    })
+  );
+  assert!(parse(r"(?u)\é").is_err());
+  assert!(parse(r"\é").is_err());
+
+  let ident = parse(r"(?u)\p{XID_Start}\p{XID_Continue}*").unwrap();
+  assert!(matches(&ident, "größe_2".as_bytes()));
+  assert!(matches(&ident, "переменная".as_bytes()));
+  assert!(!matches(&ident, "2x".as_bytes()));
+  let greek = parse(r"(?u)[\p{Greek}\s]+").unwrap();
+  assert!(matches(&greek, "καλη μερα".as_bytes()));
+  assert!(!matches(&greek, "kali mera".as_bytes()));
+  let letters = parse(r"(?u)\pL+").unwrap();
+  assert_eq!(find(&letters, "1 日本語!".as_bytes()), Some(2..11));
+  assert!(matches(parse(r"(?u)\P{L}").unwrap(), "€".as_bytes()));
+  assert!(matches(
+    parse(r"(?u)\p{ sc = greek }").unwrap(),
+    "λ".as_bytes()
+  ));
+  assert!(matches(
+    parse(r"(?u)\p{Alphabetic}").unwrap(),
+    "ß".as_bytes()
+  ));
+  assert!(parse(r"(?u)\p{gc=Greek}").is_err());
+  assert!(parse(r"(?u)\p{sc=Lu}").is_err());
+  assert!(matches(parse(r"(?u)\p{gc=Lu}").unwrap(), "Ä".as_bytes()));
+  assert_eq!(
+    parse(r"(?u)\p{Klingon}"),
+    Err(ParseError {
+      offset: 6,
+      message: "unknown property"
+    })
+  );
+  // Properties only exist in UTF-8 mode, whether bare or in a class.
+  assert_eq!(
+    parse(r"\pL"),
+    Err(ParseError {
+      offset: 2,
+      message: "unicode property outside utf-8 mode"
+    })
+  );
+  assert_eq!(
+    parse(r"[\pL]"),
+    Err(ParseError {
+      offset: 3,
+      message: "unicode property outside utf-8 mode"
+    })
+  );
+  assert!(parse(r"(?u)[\pL]").is_ok());
+
+  assert_eq!(
+    parse("a(b"),
+    Err(ParseError {
+      offset: 3,
+      message: "unclosed group"
+    })
+  );
+  let nested = "(".repeat(100000) + &")".repeat(100000);
+  assert_eq!(
+    parse(&nested),
+    Err(ParseError {
+      offset: 256,
+      message: "groups nested too deeply"
+    })
+  );
+  let shallow = "(".repeat(200) + "a" + &")".repeat(200);
+  assert!(matches(ToDfa(parse(&shallow).unwrap()), b"xa"));
 }
@@ -0,0 +1,45 @@
+use crate::*;
+
+#[derive(Debug, Clone)]
+pub struct Matcher<D: Dfa<u8>> {
+  dfa: D,
+  state: Option<D::State>,
+}
+
+impl<D: Dfa<u8>> Matcher<D> {
+  pub fn new(dfa: D) -> Self {
+    let state = Some(dfa.initial());
+    Matcher { dfa, state }
+  }
+
+  pub fn feed(&mut self, chunk: &[u8]) {
+    for &char in chunk {
+      let Some(state) = self.state.take() else {
+        return;
+      };
+      if self.dfa.decide(&state).is_some() {
+        self.state = Some(state);
+        return;
+      }
+      self.state = self.dfa.next(state, char);
+    }
+  }
+
+  pub fn is_match_so_far(&self) -> bool {
+    self.state.as_ref().is_some_and(|x| self.dfa.accept(x))
+  }
+
+  pub fn can_still_match(&self) -> bool
+  where
+    D::State: Clone + Ord,
+  {
+    self
+      .state
+      .clone()
+      .is_some_and(|x| is_empty_from(&self.dfa, x, 0..=u8::MAX).is_err())
+  }
+
+  pub fn finish(self) -> bool {
+    self.is_match_so_far()
+  }
+}
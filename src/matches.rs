@@ -2,7 +2,14 @@ use crate::*;
 
 pub fn matches<X, D: Dfa<X>>(dfa: D, str: impl IntoIterator<Item = X>) -> bool {
   let mut state = dfa.initial();
-  for char in str {
+  let mut str = str.into_iter();
+  loop {
+    if let Some(x) = dfa.decide(&state) {
+      return x;
+    }
+    let Some(char) = str.next() else {
+      break;
+    };
     state = match dfa.next(state, char) {
       Some(x) => x,
       None => return false,
@@ -0,0 +1,104 @@
+use crate::*;
+
+pub fn minimize<'a, X: Clone + Ord + 'a>(
+  dfa: &BakedDfa<X>,
+  alphabet: impl IntoIterator<Item = &'a X>,
+) -> BakedDfa<X> {
+  let alphabet = alphabet.into_iter().collect::<BTreeSet<_>>();
+  let alphabet = alphabet.into_iter().collect::<Vec<_>>();
+  let states = &dfa.0;
+
+  let mut reverse = vec![vec![vec![]; alphabet.len()]; states.len()];
+  for (from, (_, next)) in states.iter().enumerate() {
+    for (i, char) in alphabet.iter().enumerate() {
+      if let Some(&to) = next.get(char) {
+        reverse[to][i].push(from);
+      }
+    }
+  }
+
+  let mut live = vec![false; states.len()];
+  let mut todo = (0..states.len())
+    .filter(|&x| states[x].0)
+    .collect::<Vec<_>>();
+  while let Some(state) = todo.pop() {
+    if !std::mem::replace(&mut live[state], true) {
+      todo.extend(reverse[state].iter().flatten());
+    }
+  }
+
+  if states.is_empty() || !live[0] {
+    return BakedDfa(vec![(false, BTreeMap::new())]);
+  }
+
+  // Hopcroft's partition refinement over the live states; transitions into
+  // dead states are treated as missing, which puts them all in one implicit
+  // sink that is distinguishable from every live state.
+  let mut block = vec![usize::MAX; states.len()];
+  let mut blocks: Vec<Vec<usize>> = vec![];
+  for accept in [true, false] {
+    let members = (0..states.len())
+      .filter(|&x| live[x] && states[x].0 == accept)
+      .collect::<Vec<_>>();
+    if !members.is_empty() {
+      for &x in &members {
+        block[x] = blocks.len();
+      }
+      blocks.push(members);
+    }
+  }
+
+  let mut work = (0..blocks.len())
+    .flat_map(|b| (0..alphabet.len()).map(move |c| (b, c)))
+    .collect::<BTreeSet<_>>();
+  while let Some((splitter, char)) = work.pop_first() {
+    let mut touched = BTreeMap::<usize, Vec<usize>>::new();
+    for &to in &blocks[splitter] {
+      for &from in &reverse[to][char] {
+        if live[from] {
+          touched.entry(block[from]).or_default().push(from);
+        }
+      }
+    }
+    for (b, members) in touched {
+      if members.len() == blocks[b].len() {
+        continue;
+      }
+      let new = blocks.len();
+      for &x in &members {
+        block[x] = new;
+      }
+      blocks[b].retain(|&x| block[x] == b);
+      blocks.push(members);
+      for c in 0..alphabet.len() {
+        if work.contains(&(b, c)) || blocks[new].len() < blocks[b].len() {
+          work.insert((new, c));
+        } else {
+          work.insert((b, c));
+        }
+      }
+    }
+  }
+
+  let mut ids = vec![None; blocks.len()];
+  let mut order = vec![block[0]];
+  ids[block[0]] = Some(0);
+  let mut result = vec![];
+  while let Some(&b) = order.get(result.len()) {
+    let state = blocks[b][0];
+    let mut next = BTreeMap::new();
+    for &char in &alphabet {
+      if let Some(&to) = states[state].1.get(char) {
+        if live[to] {
+          let id = *ids[block[to]].get_or_insert_with(|| {
+            order.push(block[to]);
+            order.len() - 1
+          });
+          next.insert(char.clone(), id);
+        }
+      }
+    }
+    result.push((states[state].0, next));
+  }
+  BakedDfa(result)
+}
@@ -0,0 +1,497 @@
+use crate::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+  pub offset: usize,
+  pub message: &'static str,
+}
+
+impl std::fmt::Display for ParseError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{} at offset {}", self.message, self.offset)
+  }
+}
+
+impl std::error::Error for ParseError {}
+
+pub fn parse(pattern: &str) -> Result<Expr, ParseError> {
+  let mut parser = Parser {
+    input: pattern.as_bytes(),
+    offset: 0,
+    flags: Flags::default(),
+    depth: 0,
+  };
+  let expr = parser.alternation()?;
+  match parser.peek() {
+    None => Ok(expr),
+    Some(_) => Err(parser.error("unmatched `)`")),
+  }
+}
+
+// Byte classes are parsed by const functions, so that the class syntax of
+// the macros is checked at compile time.
+pub const fn parse_class(body: &str) -> Result<Class, ParseError> {
+  match class_at(body.as_bytes(), 0, None) {
+    Ok((class, _)) => Ok(class),
+    Err(x) => Err(x),
+  }
+}
+
+#[derive(Clone, Copy)]
+enum Escape {
+  Byte(u8),
+  Class(Class),
+}
+
+// Parses a class body at `offset` up to `end`, returning the class and the
+// offset after `end`.
+const fn class_at(
+  input: &[u8],
+  mut offset: usize,
+  end: Option<u8>,
+) -> Result<(Class, usize), ParseError> {
+  let negate = offset < input.len() && input[offset] == b'^';
+  if negate {
+    offset += 1;
+  }
+  let mut class = Class::EMPTY;
+  let mut first = true;
+  loop {
+    if offset == input.len() {
+      if end.is_none() {
+        break;
+      }
+      return Err(ParseError {
+        offset,
+        message: "unclosed class",
+      });
+    }
+    if matches!(end, Some(x) if x == input[offset]) && !first {
+      offset += 1;
+      break;
+    }
+    first = false;
+    let from = match class_item_at(input, offset) {
+      Ok((Escape::Byte(x), next)) => {
+        offset = next;
+        x
+      }
+      Ok((Escape::Class(x), next)) => {
+        offset = next;
+        class = class.union(x);
+        continue;
+      }
+      Err(x) => return Err(x),
+    };
+    let range = offset + 1 < input.len()
+      && input[offset] == b'-'
+      && !matches!(end, Some(x) if x == input[offset + 1]);
+    if range {
+      match class_item_at(input, offset + 1) {
+        Ok((Escape::Byte(to), next)) if from <= to => {
+          offset = next;
+          class = class.with_range(from, to);
+        }
+        Err(x) => return Err(x),
+        _ => {
+          return Err(ParseError {
+            offset: offset + 1,
+            message: "invalid range",
+          })
+        }
+      }
+    } else {
+      class = class.with(from);
+    }
+  }
+  Ok((if negate { class.negate() } else { class }, offset))
+}
+
+const fn class_item_at(input: &[u8], offset: usize) -> Result<(Escape, usize), ParseError> {
+  match input[offset] {
+    b'\\' => escape_at(input, offset + 1),
+    char => Ok((Escape::Byte(char), offset + 1)),
+  }
+}
+
+// Parses the escape after a `\` at `offset`. `\p` is not a byte or a byte
+// class, so it is handled by the parser before getting here.
+const fn escape_at(input: &[u8], offset: usize) -> Result<(Escape, usize), ParseError> {
+  if offset == input.len() {
+    return Err(ParseError {
+      offset,
+      message: "trailing `\\`",
+    });
+  }
+  let escape = match input[offset] {
+    b'd' => Escape::Class(Class::DIGIT),
+    b'D' => Escape::Class(Class::DIGIT.negate()),
+    b'w' => Escape::Class(Class::WORD),
+    b'W' => Escape::Class(Class::WORD.negate()),
+    b's' => Escape::Class(Class::SPACE),
+    b'S' => Escape::Class(Class::SPACE.negate()),
+    b'n' => Escape::Byte(b'\n'),
+    b'r' => Escape::Byte(b'\r'),
+    b't' => Escape::Byte(b'\t'),
+    b'0' => Escape::Byte(b'\0'),
+    b'p' | b'P' => {
+      return Err(ParseError {
+        offset: offset + 1,
+        message: "unicode property outside utf-8 mode",
+      })
+    }
+    b'x' if offset + 2 < input.len() => {
+      match (hex_digit(input[offset + 1]), hex_digit(input[offset + 2])) {
+        (Some(high), Some(low)) => return Ok((Escape::Byte(high << 4 | low), offset + 3)),
+        _ => {
+          return Err(ParseError {
+            offset: offset + 1,
+            message: "invalid hex escape",
+          })
+        }
+      }
+    }
+    b'x' => {
+      return Err(ParseError {
+        offset: offset + 1,
+        message: "invalid hex escape",
+      })
+    }
+    0x80.. => {
+      return Err(ParseError {
+        offset,
+        message: "escaped non-ASCII character",
+      })
+    }
+    char => Escape::Byte(char),
+  };
+  Ok((escape, offset + 1))
+}
+
+const fn hex_digit(char: u8) -> Option<u8> {
+  match char {
+    b'0'..=b'9' => Some(char - b'0'),
+    b'a'..=b'f' => Some(char - b'a' + 10),
+    b'A'..=b'F' => Some(char - b'A' + 10),
+    _ => None,
+  }
+}
+
+#[derive(Clone, Copy, Default)]
+struct Flags {
+  multi_line: bool,
+  crlf: bool,
+  utf8: bool,
+}
+
+// Patterns may come from untrusted input, so groups nest only this deep
+// rather than until the recursive parser overflows the stack.
+const MAX_DEPTH: usize = 256;
+
+struct Parser<'a> {
+  input: &'a [u8],
+  offset: usize,
+  flags: Flags,
+  depth: usize,
+}
+
+impl Parser<'_> {
+  fn peek(&self) -> Option<u8> {
+    self.input.get(self.offset).copied()
+  }
+
+  fn eat(&mut self, str: &[u8]) -> bool {
+    if self.input[self.offset..].starts_with(str) {
+      self.offset += str.len();
+      true
+    } else {
+      false
+    }
+  }
+
+  fn error(&self, message: &'static str) -> ParseError {
+    ParseError {
+      offset: self.offset,
+      message,
+    }
+  }
+
+  fn alternation(&mut self) -> Result<Expr, ParseError> {
+    let mut items = vec![self.concatenation()?];
+    while self.eat(b"|") {
+      items.push(self.concatenation()?);
+    }
+    Ok(Expr::or_all(items))
+  }
+
+  fn concatenation(&mut self) -> Result<Expr, ParseError> {
+    let mut items = vec![];
+    while !matches!(self.peek(), None | Some(b'|' | b')')) {
+      let x = self.repetition()?;
+      match (items.last_mut(), x) {
+        (Some(Expr::Literal(a)), Expr::Literal(b)) => a.extend(b),
+        (_, x) => items.push(x),
+      }
+    }
+    Ok(Expr::concat_all(items))
+  }
+
+  fn repetition(&mut self) -> Result<Expr, ParseError> {
+    let mut x = self.atom()?;
+    loop {
+      x = match self.peek() {
+        Some(b'?') => {
+          self.offset += 1;
+          Expr::Or(Box::new(Expr::Empty), Box::new(x))
+        }
+        Some(b'*') => {
+          self.offset += 1;
+          Expr::Or(Box::new(Expr::Empty), Box::new(Expr::Plus(Box::new(x))))
+        }
+        Some(b'+') => {
+          self.offset += 1;
+          Expr::Plus(Box::new(x))
+        }
+        Some(b'{') => {
+          let (min, max) = self.counts()?;
+          Expr::Repeat(Box::new(x), min, max)
+        }
+        _ => return Ok(x),
+      };
+    }
+  }
+
+  fn counts(&mut self) -> Result<(usize, Option<usize>), ParseError> {
+    let start = self.offset;
+    self.offset += 1;
+    let min = self.number();
+    let max = if self.eat(b",") { self.number() } else { min };
+    match min {
+      Some(min) if self.eat(b"}") && max.is_none_or(|max| min <= max) => Ok((min, max)),
+      _ => {
+        self.offset = start;
+        Err(self.error("invalid repetition"))
+      }
+    }
+  }
+
+  fn number(&mut self) -> Option<usize> {
+    let start = self.offset;
+    while self.peek().is_some_and(|x| x.is_ascii_digit()) {
+      self.offset += 1;
+    }
+    std::str::from_utf8(&self.input[start..self.offset])
+      .ok()?
+      .parse()
+      .ok()
+  }
+
+  fn atom(&mut self) -> Result<Expr, ParseError> {
+    let start = self.offset;
+    let Some(char) = self.peek() else {
+      return Err(self.error("unexpected end of pattern"));
+    };
+    self.offset += 1;
+    Ok(match char {
+      b'(' if self.flag_group(b')') => Expr::Empty,
+      b'(' if self.depth == MAX_DEPTH => {
+        self.offset = start;
+        return Err(self.error("groups nested too deeply"));
+      }
+      b'(' => {
+        let flags = self.flags;
+        let wrap: Box<dyn FnOnce(Expr) -> Expr> = if self.eat(b"?=") {
+          Box::new(|x| Expr::LookAhead(Box::new(x)))
+        } else if self.eat(b"?!") {
+          Box::new(|x| Expr::LookAhead(Box::new(Expr::Not(Box::new(x)))))
+        } else if self.eat(b"?<=") {
+          Box::new(|x| Expr::LookBehind(Box::new(x)))
+        } else if self.eat(b"?<!") {
+          Box::new(|x| Expr::LookBehind(Box::new(Expr::Not(Box::new(x)))))
+        } else if self.eat(b"?:") || self.flag_group(b':') {
+          Box::new(|x| x)
+        } else if self.eat(b"?<") {
+          let name = self.group_name()?;
+          Box::new(|x| Expr::Group(Some(name), Box::new(x)))
+        } else {
+          Box::new(|x| Expr::Group(None, Box::new(x)))
+        };
+        self.depth += 1;
+        let x = self.alternation()?;
+        if !self.eat(b")") {
+          return Err(self.error("unclosed group"));
+        }
+        self.depth -= 1;
+        self.flags = flags;
+        wrap(x)
+      }
+      b'[' if self.flags.utf8 => Expr::Utf8Class(self.utf8_class()?),
+      b'[' => Expr::Class(self.class(Some(b']'))?),
+      b'.' if self.flags.utf8 => Expr::Utf8Class(Utf8Class::any()),
+      b'.' => Expr::Dot,
+      b'^' if self.flags.multi_line => Expr::LineStart(self.flags.crlf),
+      b'$' if self.flags.multi_line => Expr::LineEnd(self.flags.crlf),
+      b'^' => Expr::Start,
+      b'$' => Expr::End,
+      b'\\' if self.eat(b"A") => Expr::Start,
+      b'\\' if self.eat(b"z") => Expr::End,
+      b'\\' if self.eat(b"b") => Expr::WordBoundary,
+      b'\\' if self.eat(b"B") => Expr::NotWordBoundary,
+      b'\\' if self.flags.utf8 && self.eat(b"p") => Expr::Utf8Class(self.property()?),
+      b'\\' if self.flags.utf8 && self.eat(b"P") => Expr::Utf8Class(self.property()?.negate()),
+      b'\\' if self.flags.utf8 => match self.escape()? {
+        Escape::Byte(x) => Expr::Literal((x as char).to_string().into_bytes()),
+        Escape::Class(x) => Expr::Utf8Class(Utf8Class::from_class(x)),
+      },
+      b'\\' => match self.escape()? {
+        Escape::Byte(x) => Expr::Literal(vec![x]),
+        Escape::Class(x) => Expr::Class(x),
+      },
+      b'?' | b'*' | b'+' | b'{' => {
+        self.offset = start;
+        return Err(self.error("repetition of nothing"));
+      }
+      0x80.. if self.flags.utf8 => {
+        self.offset = start;
+        Expr::Literal(self.char().to_string().into_bytes())
+      }
+      char => Expr::Literal(vec![char]),
+    })
+  }
+
+  // `(?m)` turns `^` and `$` into line anchors until the end of the enclosing
+  // group, `(?m:...)` only inside it; `R` additionally makes them treat
+  // `\r\n` as a line break, and `u` makes `.` and classes match whole UTF-8
+  // encoded code points.
+  fn flag_group(&mut self, end: u8) -> bool {
+    let start = self.offset;
+    let mut flags = self.flags;
+    if self.eat(b"?") {
+      while let Some(flag @ (b'm' | b'R' | b'u')) = self.peek() {
+        self.offset += 1;
+        flags.multi_line |= flag == b'm';
+        flags.crlf |= flag == b'R';
+        flags.utf8 |= flag == b'u';
+      }
+      if self.offset > start + 1 && self.eat(&[end]) {
+        self.flags = flags;
+        return true;
+      }
+    }
+    self.offset = start;
+    false
+  }
+
+  fn group_name(&mut self) -> Result<String, ParseError> {
+    let start = self.offset;
+    while self
+      .peek()
+      .is_some_and(|x| x.is_ascii_alphanumeric() || x == b'_')
+    {
+      self.offset += 1;
+    }
+    let name = String::from_utf8(self.input[start..self.offset].to_vec()).unwrap();
+    if name.is_empty() || !self.eat(b">") {
+      return Err(self.error("invalid group name"));
+    }
+    Ok(name)
+  }
+
+  fn class(&mut self, end: Option<u8>) -> Result<Class, ParseError> {
+    let (class, offset) = class_at(self.input, self.offset, end)?;
+    self.offset = offset;
+    Ok(class)
+  }
+
+  fn utf8_class(&mut self) -> Result<Utf8Class, ParseError> {
+    let negate = self.eat(b"^");
+    let mut class = Utf8Class::new(vec![]);
+    let mut first = true;
+    loop {
+      match self.peek() {
+        None => return Err(self.error("unclosed class")),
+        Some(b']') if !first => {
+          self.offset += 1;
+          break;
+        }
+        Some(_) => first = false,
+      }
+      let from = match self.utf8_class_item()? {
+        Ok(x) => x,
+        Err(x) => {
+          class = class.union(&x);
+          continue;
+        }
+      };
+      let range =
+        self.peek() == Some(b'-') && self.input.get(self.offset + 1).is_some_and(|&x| x != b']');
+      if range {
+        self.offset += 1;
+        let start = self.offset;
+        match self.utf8_class_item()? {
+          Ok(to) if from <= to => class = class.union(&Utf8Class::new(vec![(from, to)])),
+          _ => {
+            self.offset = start;
+            return Err(self.error("invalid range"));
+          }
+        }
+      } else {
+        class = class.union(&Utf8Class::new(vec![(from, from)]));
+      }
+    }
+    Ok(if negate { class.negate() } else { class })
+  }
+
+  // A code point, or the class of an escape like `\w`.
+  fn utf8_class_item(&mut self) -> Result<Result<char, Utf8Class>, ParseError> {
+    if !self.eat(b"\\") {
+      return Ok(Ok(self.char()));
+    }
+    if self.eat(b"p") {
+      return Ok(Err(self.property()?));
+    }
+    if self.eat(b"P") {
+      return Ok(Err(self.property()?.negate()));
+    }
+    Ok(match self.escape()? {
+      Escape::Byte(x) => Ok(x as char),
+      Escape::Class(x) => Err(Utf8Class::from_class(x)),
+    })
+  }
+
+  fn char(&mut self) -> char {
+    let rest = std::str::from_utf8(&self.input[self.offset..]).unwrap();
+    let char = rest.chars().next().unwrap();
+    self.offset += char.len_utf8();
+    char
+  }
+
+  // `\pL` or `\p{Greek}`, with the name matched loosely.
+  fn property(&mut self) -> Result<Utf8Class, ParseError> {
+    let start = self.offset;
+    let name = if self.eat(b"{") {
+      let end = self.input[self.offset..].iter().position(|&x| x == b'}');
+      let Some(end) = end else {
+        return Err(self.error("unclosed property"));
+      };
+      self.offset += end + 1;
+      &self.input[start + 1..self.offset - 1]
+    } else if self.peek().is_some() {
+      self.offset = start + self.char().len_utf8();
+      &self.input[start..self.offset]
+    } else {
+      return Err(self.error("trailing `\\p`"));
+    };
+    let name = std::str::from_utf8(name).unwrap();
+    Utf8Class::property(name).ok_or(ParseError {
+      offset: start,
+      message: "unknown property",
+    })
+  }
+
+  fn escape(&mut self) -> Result<Escape, ParseError> {
+    let (escape, offset) = escape_at(self.input, self.offset)?;
+    self.offset = offset;
+    Ok(escape)
+  }
+}
@@ -12,61 +12,113 @@ macro_rules! dfa {
     };
 }
 
+// The leading `@[..]` holds the flags set by `(?m)` or `(?mR)`, which decide
+// what `^` and `$` mean.
 #[macro_export]
 macro_rules! _regex {
-  ( (?= $($x:tt)*) ) => {
-    LookAhead(_regex!($($x)*))
+  ( @$f:tt ) => {
+    FromDfa(Empty)
   };
-  ( (?! $($x:tt)*) ) => {
-    LookAhead(Not(_regex!($($x)*)))
+  ( @$f:tt (?m) $($x:tt)* ) => {
+    _regex!(@[m] $($x)*)
   };
-  ( (?<= $($x:tt)*) ) => {
-    LookBehind(_regex!($($x)*))
+  ( @$f:tt (?mR) $($x:tt)* ) => {
+    _regex!(@[mR] $($x)*)
   };
-  ( (?<! $($x:tt)*) ) => {
-    LookBehind(Not(_regex!($($x)*)))
+  ( @$f:tt (?= $($x:tt)*) ) => {
+    LookAhead(_regex!(@$f $($x)*))
   };
-  ( ($($x:tt)*) ) => {
-    _regex!($($x)*)
+  ( @$f:tt (?! $($x:tt)*) ) => {
+    LookAhead(Not(_regex!(@$f $($x)*)))
   };
-  ( {$x:expr} ) => {
+  ( @$f:tt (?<= $($x:tt)*) ) => {
+    LookBehind(_regex!(@$f $($x)*))
+  };
+  ( @$f:tt (?<! $($x:tt)*) ) => {
+    LookBehind(Not(_regex!(@$f $($x)*)))
+  };
+  ( @$f:tt (?: $($x:tt)*) ) => {
+    _regex!(@$f $($x)*)
+  };
+  ( @$f:tt (?< $n:ident > $($x:tt)*) ) => {
+    Group(Some(stringify!($n)), _regex!(@$f $($x)*))
+  };
+  ( @$f:tt ($($x:tt)*) ) => {
+    Group(None, _regex!(@$f $($x)*))
+  };
+  ( @$f:tt [$s:literal] ) => {
+    FromDfa(const {
+      match parse_class($s) {
+        Ok(x) => x,
+        Err(x) => panic!("{}", x.message),
+      }
+    })
+  };
+  ( @$f:tt [$($x:tt)*] ) => {
+    compile_error!("a class is written as a string literal, like [\"a-z\"]")
+  };
+  ( @$f:tt {$x:expr} ) => {
     $x
   };
-  ( $x:ident ) => {
+  ( @$f:tt $x:ident ) => {
     FromDfa(stringify!($x).as_bytes())
   };
-  ( $x:literal ) => {
+  ( @$f:tt $x:literal ) => {
     FromDfa($x)
   };
-  ( ^ ) => {
+  ( @[m] ^ ) => {
+    LineStart { crlf: false }
+  };
+  ( @[mR] ^ ) => {
+    LineStart { crlf: true }
+  };
+  ( @$f:tt ^ ) => {
     Start
   };
-  ( $ ) => {
+  ( @[m] $ ) => {
+    LineEnd { crlf: false }
+  };
+  ( @[mR] $ ) => {
+    LineEnd { crlf: true }
+  };
+  ( @$f:tt $ ) => {
     End
   };
-  ( . ) => {
+  ( @$f:tt . ) => {
     FromDfa(Dot)
   };
-  ( .. ) => {
+  ( @$f:tt .. ) => {
     Concat(FromDfa(Dot), FromDfa(Dot))
   };
-  ( ... ) => {
+  ( @$f:tt ... ) => {
     Concat(FromDfa(Dot), Concat(FromDfa(Dot), FromDfa(Dot)))
   };
-  ( $x:tt | $($y:tt)* ) => {
-    Or(_regex!($x), _regex!($($y)*))
+  ( @$f:tt $x:tt | $($y:tt)* ) => {
+    Or(_regex!(@$f $x), _regex!(@$f $($y)*))
+  };
+  ( @$f:tt $x:tt ? $($y:tt)* ) => {
+    _regex!(@$f {Or(FromDfa(Empty), _regex!(@$f $x))} $($y)*)
+  };
+  ( @$f:tt $x:tt * $($y:tt)* ) => {
+    _regex!(@$f {Or(FromDfa(Empty), Plus(_regex!(@$f $x)))} $($y)*)
+  };
+  ( @$f:tt $x:tt + $($y:tt)* ) => {
+    _regex!(@$f {Plus(_regex!(@$f $x))} $($y)*)
+  };
+  ( @$f:tt $x:tt {$n:literal} $($y:tt)* ) => {
+    _regex!(@$f {Repeat { inner: _regex!(@$f $x), min: $n, max: Some($n) }} $($y)*)
   };
-  ( $x:tt ? $($y:tt)* ) => {
-    _regex!({Or(FromDfa(Empty), _regex!($x))} $($y)*)
+  ( @$f:tt $x:tt {$n:literal,} $($y:tt)* ) => {
+    _regex!(@$f {Repeat { inner: _regex!(@$f $x), min: $n, max: None }} $($y)*)
   };
-  ( $x:tt * $($y:tt)* ) => {
-    _regex!({Or(FromDfa(Empty), Plus(_regex!($x)))} $($y)*)
+  ( @$f:tt $x:tt {$n:literal, $m:literal} $($y:tt)* ) => {
+    _regex!(@$f {Repeat { inner: _regex!(@$f $x), min: $n, max: Some($m) }} $($y)*)
   };
-  ( $x:tt + $($y:tt)* ) => {
-    _regex!({Plus(_regex!($x))} $($y)*)
+  ( @$f:tt $x:tt $($y:tt)+ ) => {
+    Concat(_regex!(@$f $x), _regex!(@$f $($y)+))
   };
-  ( $x:tt $($y:tt)+ ) => {
-    Concat(_regex!($x), _regex!($($y)+))
+  ( $($x:tt)* ) => {
+    _regex!(@[] $($x)*)
   };
 }
 
@@ -91,15 +143,58 @@ pub struct Anything;
 #[derive(Debug, Clone, Copy)]
 pub struct Dot;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Class(pub [u64; 4]);
+
+// A set of code points, matching one UTF-8 encoded code point from the set.
+// The byte-level form is computed once, by `Utf8Class::new`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Utf8Class {
+  pub ranges: Vec<(char, char)>,
+  pub(crate) sequences: Vec<Vec<(u8, u8)>>,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Plus<A>(pub A);
 
+#[derive(Debug, Clone, Copy)]
+pub struct Repeat<A> {
+  pub inner: A,
+  pub min: usize,
+  pub max: Option<usize>,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Start;
 
 #[derive(Debug, Clone, Copy)]
 pub struct End;
 
+#[derive(Debug, Clone, Copy)]
+pub struct LineStart {
+  pub crlf: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LineEnd {
+  pub crlf: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AtOffset(pub usize);
+
+#[derive(Debug, Clone, Copy)]
+pub struct AtOffsetFromEnd(pub usize);
+
+#[derive(Debug, Clone, Copy)]
+pub struct AtOffsetMod(pub usize, pub usize);
+
+#[derive(Debug, Clone, Copy)]
+pub struct WordBoundary;
+
+#[derive(Debug, Clone, Copy)]
+pub struct NotWordBoundary;
+
 #[derive(Debug, Clone, Copy)]
 pub struct LookAhead<A>(pub A);
 
@@ -114,3 +209,6 @@ pub struct FromDfa<A>(pub A);
 
 #[derive(Debug, Clone, Copy)]
 pub struct And<A, B>(pub A, pub B);
+
+#[derive(Debug, Clone, Copy)]
+pub struct Group<A>(pub Option<&'static str>, pub A);
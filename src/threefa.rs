@@ -1,16 +1,22 @@
 use crate::*;
 
 mod and;
+mod at_offset;
 mod concat;
 mod end;
 mod from_dfa;
+mod group;
+mod line_end;
+mod line_start;
 mod lookahead;
 mod lookbehind;
 mod not;
 mod or;
 mod plus;
+mod repeat;
 mod start;
 mod to_dfa;
+mod word_boundary;
 
 pub trait ThreeFA<X> {
   type Pre: Finite;
@@ -21,6 +27,55 @@ pub trait ThreeFA<X> {
   fn step_active(&self, state: Self::Active, char: X) -> Option<Self::Active>;
   fn step_post(&self, state: Self::Post, char: X) -> Option<Self::Post>;
   fn accept(&self, state: &Self::Post) -> bool;
+  // `Some(x)` once a post state accepts (`true`) or rejects (`false`)
+  // whatever follows, so that a search can stop reading.
+  fn decide(&self, _state: &Self::Post) -> Option<bool> {
+    None
+  }
   fn enter(&self, state: Self::Pre) -> Option<Self::Active>;
   fn exit(&self, state: Self::Active) -> Option<Self::Post>;
 }
+
+impl<X, T: ThreeFA<X> + ?Sized> ThreeFA<X> for &T {
+  type Pre = T::Pre;
+  type Active = T::Active;
+  type Post = T::Post;
+  fn initial(&self) -> Self::Pre {
+    (*self).initial()
+  }
+  fn step_pre(&self, state: Self::Pre, char: X) -> Option<Self::Pre> {
+    (*self).step_pre(state, char)
+  }
+  fn step_active(&self, state: Self::Active, char: X) -> Option<Self::Active> {
+    (*self).step_active(state, char)
+  }
+  fn step_post(&self, state: Self::Post, char: X) -> Option<Self::Post> {
+    (*self).step_post(state, char)
+  }
+  fn accept(&self, state: &Self::Post) -> bool {
+    (*self).accept(state)
+  }
+  fn decide(&self, state: &Self::Post) -> Option<bool> {
+    (*self).decide(state)
+  }
+  fn enter(&self, state: Self::Pre) -> Option<Self::Active> {
+    (*self).enter(state)
+  }
+  fn exit(&self, state: Self::Active) -> Option<Self::Post> {
+    (*self).exit(state)
+  }
+}
+
+// Decides a set of states that accepts when any of them does: `true` once one
+// of them is decided `true`, `false` once all of them are decided `false`.
+pub(crate) fn any_decided(decisions: impl IntoIterator<Item = Option<bool>>) -> Option<bool> {
+  let mut all_false = true;
+  for x in decisions {
+    match x {
+      Some(true) => return Some(true),
+      Some(false) => {}
+      None => all_false = false,
+    }
+  }
+  all_false.then_some(false)
+}
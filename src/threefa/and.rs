@@ -28,6 +28,14 @@ impl<X: Clone, A: ThreeFA<X>, B: ThreeFA<X>> ThreeFA<X> for And<A, B> {
   fn accept(&self, state: &Self::Post) -> bool {
     self.0.accept(&state.0) && self.1.accept(&state.1)
   }
+  fn decide(&self, state: &Self::Post) -> Option<bool> {
+    match (self.0.decide(&state.0), self.1.decide(&state.1)) {
+      (Some(false), _) | (_, Some(false)) => Some(false),
+      (Some(true), Some(true)) => Some(true),
+      _ => None,
+    }
+  }
+
   fn enter(&self, state: Self::Pre) -> Option<Self::Active> {
     Some((self.0.enter(state.0)?, self.1.enter(state.1)?))
   }
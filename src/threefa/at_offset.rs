@@ -0,0 +1,96 @@
+use crate::*;
+
+// Pre counts the bytes seen so far, stopping once it is past `n`.
+impl<X> ThreeFA<X> for AtOffset {
+  type Pre = usize;
+  type Active = ();
+  type Post = ();
+  fn initial(&self) -> Self::Pre {
+    0
+  }
+  fn step_pre(&self, state: Self::Pre, _: X) -> Option<Self::Pre> {
+    Some(self.0.min(state) + 1)
+  }
+  fn step_active(&self, _: Self::Active, _: X) -> Option<Self::Active> {
+    None
+  }
+  fn step_post(&self, _: Self::Post, _: X) -> Option<Self::Post> {
+    Some(())
+  }
+  fn accept(&self, _: &Self::Post) -> bool {
+    true
+  }
+  fn decide(&self, _: &Self::Post) -> Option<bool> {
+    Some(true)
+  }
+
+  fn enter(&self, state: Self::Pre) -> Option<Self::Active> {
+    (state == self.0).then_some(())
+  }
+  fn exit(&self, _: Self::Active) -> Option<Self::Post> {
+    Some(())
+  }
+}
+
+// Post counts the bytes left after the position, dying once there are more
+// than `n`.
+impl<X> ThreeFA<X> for AtOffsetFromEnd {
+  type Pre = ();
+  type Active = ();
+  type Post = usize;
+  fn initial(&self) -> Self::Pre {}
+  fn step_pre(&self, _: Self::Pre, _: X) -> Option<Self::Pre> {
+    Some(())
+  }
+  fn step_active(&self, _: Self::Active, _: X) -> Option<Self::Active> {
+    None
+  }
+  fn step_post(&self, state: Self::Post, _: X) -> Option<Self::Post> {
+    (state < self.0).then_some(state + 1)
+  }
+  fn accept(&self, state: &Self::Post) -> bool {
+    *state == self.0
+  }
+  fn enter(&self, _: Self::Pre) -> Option<Self::Active> {
+    Some(())
+  }
+  fn exit(&self, _: Self::Active) -> Option<Self::Post> {
+    Some(0)
+  }
+}
+
+// Pre is the offset so far modulo `k`. Like `x % 0 == x` in the usual
+// convention, `k == 0` makes this `AtOffset(n)`.
+impl<X> ThreeFA<X> for AtOffsetMod {
+  type Pre = usize;
+  type Active = ();
+  type Post = ();
+  fn initial(&self) -> Self::Pre {
+    0
+  }
+  fn step_pre(&self, state: Self::Pre, _: X) -> Option<Self::Pre> {
+    Some(match self.1 {
+      0 => self.0.min(state) + 1,
+      k => (state + 1) % k,
+    })
+  }
+  fn step_active(&self, _: Self::Active, _: X) -> Option<Self::Active> {
+    None
+  }
+  fn step_post(&self, _: Self::Post, _: X) -> Option<Self::Post> {
+    Some(())
+  }
+  fn accept(&self, _: &Self::Post) -> bool {
+    true
+  }
+  fn decide(&self, _: &Self::Post) -> Option<bool> {
+    Some(true)
+  }
+
+  fn enter(&self, state: Self::Pre) -> Option<Self::Active> {
+    (state == self.0).then_some(())
+  }
+  fn exit(&self, _: Self::Active) -> Option<Self::Post> {
+    Some(())
+  }
+}
@@ -67,6 +67,18 @@ where
       .iter()
       .any(|x| self.0.accept(&x.0) && self.1.accept(&x.1))
   }
+  fn decide(&self, state: &Self::Post) -> Option<bool> {
+    any_decided(
+      state
+        .iter()
+        .map(|x| match (self.0.decide(&x.0), self.1.decide(&x.1)) {
+          (Some(false), _) | (_, Some(false)) => Some(false),
+          (Some(true), Some(true)) => Some(true),
+          _ => None,
+        }),
+    )
+  }
+
   fn enter(&self, state: Self::Pre) -> Option<Self::Active> {
     let state = (self.0.enter(state.0)?, state.1);
     Some((
@@ -4,9 +4,7 @@ impl<X> ThreeFA<X> for End {
   type Pre = ();
   type Active = ();
   type Post = ();
-  fn initial(&self) -> Self::Pre {
-    ()
-  }
+  fn initial(&self) -> Self::Pre {}
   fn step_pre(&self, _: Self::Pre, _: X) -> Option<Self::Pre> {
     Some(())
   }
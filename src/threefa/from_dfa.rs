@@ -4,9 +4,7 @@ impl<X, A: Dfa<X>> ThreeFA<X> for FromDfa<A> {
   type Pre = ();
   type Active = A::State;
   type Post = ();
-  fn initial(&self) -> Self::Pre {
-    ()
-  }
+  fn initial(&self) -> Self::Pre {}
   fn step_pre(&self, _: Self::Pre, _: X) -> Option<Self::Pre> {
     Some(())
   }
@@ -19,6 +17,10 @@ impl<X, A: Dfa<X>> ThreeFA<X> for FromDfa<A> {
   fn accept(&self, _: &Self::Post) -> bool {
     true
   }
+  fn decide(&self, _: &Self::Post) -> Option<bool> {
+    Some(true)
+  }
+
   fn enter(&self, _: Self::Pre) -> Option<Self::Active> {
     Some(self.0.initial())
   }
@@ -0,0 +1,32 @@
+use crate::*;
+
+impl<X, A: ThreeFA<X>> ThreeFA<X> for Group<A> {
+  type Pre = A::Pre;
+  type Active = A::Active;
+  type Post = A::Post;
+  fn initial(&self) -> Self::Pre {
+    self.1.initial()
+  }
+  fn step_pre(&self, state: Self::Pre, char: X) -> Option<Self::Pre> {
+    self.1.step_pre(state, char)
+  }
+  fn step_active(&self, state: Self::Active, char: X) -> Option<Self::Active> {
+    self.1.step_active(state, char)
+  }
+  fn step_post(&self, state: Self::Post, char: X) -> Option<Self::Post> {
+    self.1.step_post(state, char)
+  }
+  fn accept(&self, state: &Self::Post) -> bool {
+    self.1.accept(state)
+  }
+  fn decide(&self, state: &Self::Post) -> Option<bool> {
+    self.1.decide(state)
+  }
+
+  fn enter(&self, state: Self::Pre) -> Option<Self::Active> {
+    self.1.enter(state)
+  }
+  fn exit(&self, state: Self::Active) -> Option<Self::Post> {
+    self.1.exit(state)
+  }
+}
@@ -0,0 +1,40 @@
+use crate::*;
+use std::borrow::Borrow;
+
+// Pre and Active remember whether the previous byte was `\r`, so that with
+// `crlf` the position between `\r` and `\n` is not a line end. Post holds
+// that until the next byte has been checked.
+impl<X: Borrow<u8>> ThreeFA<X> for LineEnd {
+  type Pre = bool;
+  type Active = bool;
+  type Post = Option<bool>;
+  fn initial(&self) -> Self::Pre {
+    false
+  }
+  fn step_pre(&self, _: Self::Pre, char: X) -> Option<Self::Pre> {
+    Some(self.crlf && *char.borrow() == b'\r')
+  }
+  fn step_active(&self, _: Self::Active, _: X) -> Option<Self::Active> {
+    None
+  }
+  fn step_post(&self, state: Self::Post, char: X) -> Option<Self::Post> {
+    let char = *char.borrow();
+    match state {
+      Some(cr) if !((char == b'\n' && !cr) || (self.crlf && char == b'\r')) => None,
+      _ => Some(None),
+    }
+  }
+  fn accept(&self, _: &Self::Post) -> bool {
+    true
+  }
+  fn decide(&self, state: &Self::Post) -> Option<bool> {
+    state.is_none().then_some(true)
+  }
+
+  fn enter(&self, state: Self::Pre) -> Option<Self::Active> {
+    Some(state)
+  }
+  fn exit(&self, state: Self::Active) -> Option<Self::Post> {
+    Some(Some(state))
+  }
+}
@@ -0,0 +1,36 @@
+use crate::*;
+use std::borrow::Borrow;
+
+// Pre is (at a line start, just after `\r`); with `crlf` the position
+// between `\r` and `\n` is not a line start, which Post checks.
+impl<X: Borrow<u8>> ThreeFA<X> for LineStart {
+  type Pre = (bool, bool);
+  type Active = bool;
+  type Post = bool;
+  fn initial(&self) -> Self::Pre {
+    (true, false)
+  }
+  fn step_pre(&self, _: Self::Pre, char: X) -> Option<Self::Pre> {
+    let cr = self.crlf && *char.borrow() == b'\r';
+    Some((*char.borrow() == b'\n' || cr, cr))
+  }
+  fn step_active(&self, _: Self::Active, _: X) -> Option<Self::Active> {
+    None
+  }
+  fn step_post(&self, state: Self::Post, char: X) -> Option<Self::Post> {
+    (!state || *char.borrow() != b'\n').then_some(false)
+  }
+  fn accept(&self, _: &Self::Post) -> bool {
+    true
+  }
+  fn decide(&self, state: &Self::Post) -> Option<bool> {
+    (!state).then_some(true)
+  }
+
+  fn enter(&self, (start, cr): Self::Pre) -> Option<Self::Active> {
+    start.then_some(cr)
+  }
+  fn exit(&self, state: Self::Active) -> Option<Self::Post> {
+    Some(state)
+  }
+}
@@ -39,6 +39,13 @@ where
   fn accept(&self, state: &Self::Post) -> bool {
     state.1.iter().any(|x| self.0.accept(x))
   }
+  fn decide(&self, state: &Self::Post) -> Option<bool> {
+    match any_decided(state.1.iter().map(|x| self.0.decide(x))) {
+      Some(false) if !state.0.is_empty() => None,
+      x => x,
+    }
+  }
+
   fn enter(&self, state: Self::Pre) -> Option<Self::Active> {
     Some(state)
   }
@@ -50,6 +50,10 @@ where
   fn accept(&self, state: &Self::Post) -> bool {
     state.iter().any(|x| self.0.accept(x))
   }
+  fn decide(&self, state: &Self::Post) -> Option<bool> {
+    any_decided(state.iter().map(|x| self.0.decide(x)))
+  }
+
   fn enter(&self, state: Self::Pre) -> Option<Self::Active> {
     Some(state)
   }
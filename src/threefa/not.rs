@@ -17,8 +17,15 @@ impl<X: Clone, A: ThreeFA<X>> ThreeFA<X> for Not<A> {
     Some(state.and_then(|x| self.0.step_post(x, char)))
   }
   fn accept(&self, state: &Self::Post) -> bool {
-    !state.as_ref().map_or(false, |x| self.0.accept(x))
+    !state.as_ref().is_some_and(|x| self.0.accept(x))
   }
+  fn decide(&self, state: &Self::Post) -> Option<bool> {
+    match state {
+      Some(x) => self.0.decide(x).map(|x| !x),
+      None => Some(true),
+    }
+  }
+
   fn enter(&self, state: Self::Pre) -> Option<Self::Active> {
     Some(state.and_then(|x| self.0.enter(x)))
   }
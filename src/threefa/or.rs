@@ -35,9 +35,19 @@ impl<X: Clone, A: ThreeFA<X>, B: ThreeFA<X>> ThreeFA<X> for Or<A, B> {
     }
   }
   fn accept(&self, state: &Self::Post) -> bool {
-    state.0.as_ref().map_or(false, |x| self.0.accept(x))
-      || state.1.as_ref().map_or(false, |x| self.1.accept(x))
+    state.0.as_ref().is_some_and(|x| self.0.accept(x))
+      || state.1.as_ref().is_some_and(|x| self.1.accept(x))
   }
+  fn decide(&self, state: &Self::Post) -> Option<bool> {
+    let a = state.0.as_ref().map_or(Some(false), |x| self.0.decide(x));
+    let b = state.1.as_ref().map_or(Some(false), |x| self.1.decide(x));
+    match (a, b) {
+      (Some(true), _) | (_, Some(true)) => Some(true),
+      (Some(false), Some(false)) => Some(false),
+      _ => None,
+    }
+  }
+
   fn enter(&self, state: Self::Pre) -> Option<Self::Active> {
     let a = state.0.and_then(|x| self.0.enter(x));
     let b = state.1.and_then(|x| self.1.enter(x));
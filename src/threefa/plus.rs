@@ -61,10 +61,21 @@ where
     }
   }
   fn accept(&self, state: &Self::Post) -> bool {
-    state
-      .iter()
-      .any(|x| x.into_iter().all(|x| self.0.accept(x)))
+    state.iter().any(|x| x.iter().all(|x| self.0.accept(x)))
   }
+  fn decide(&self, state: &Self::Post) -> Option<bool> {
+    any_decided(state.iter().map(|x| {
+      let mut x = x.iter().map(|x| self.0.decide(x));
+      if x.clone().any(|x| x == Some(false)) {
+        Some(false)
+      } else if x.all(|x| x == Some(true)) {
+        Some(true)
+      } else {
+        None
+      }
+    }))
+  }
+
   fn enter(&self, state: Self::Pre) -> Option<Self::Active> {
     Some((
       Some(state.clone()),
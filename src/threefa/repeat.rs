@@ -0,0 +1,142 @@
+use crate::*;
+
+type Iterations<E, S> = BTreeSet<(usize, BTreeSet<E>, S)>;
+
+impl<A> Repeat<A> {
+  fn threefa_closure<X, P: Clone, S: Clone + Ord, E: Clone + Ord>(
+    &self,
+    pre: &Option<P>,
+    mut state: Iterations<E, S>,
+  ) -> Iterations<E, S>
+  where
+    A: ThreeFA<X, Pre = P, Active = S, Post = E>,
+  {
+    let Some(pre) = pre else {
+      return state;
+    };
+    let mut todo = state.iter().cloned().collect::<Vec<_>>();
+    while let Some((n, mut e, x)) = todo.pop() {
+      let next = (|| {
+        let n = self.more(n)?;
+        e.insert(self.inner.exit(x)?);
+        Some((n, e, self.inner.enter(pre.clone())?))
+      })();
+      if let Some(x) = next {
+        if state.insert(x.clone()) {
+          todo.push(x);
+        }
+      }
+    }
+    state
+  }
+}
+
+impl<X: Clone, A: ThreeFA<X>> ThreeFA<X> for Repeat<A>
+where
+  A::Pre: Clone,
+  A::Active: Ord + Clone,
+  A::Post: Ord + Clone,
+{
+  // With `min == 0` the empty repeat matches whatever came before, so the
+  // pre state stays alive after the inner one dies, as it does for `Or`.
+  type Pre = Option<A::Pre>;
+  type Active = (Option<A::Pre>, bool, Iterations<A::Post, A::Active>);
+  type Post = BTreeSet<BTreeSet<A::Post>>;
+  fn initial(&self) -> Self::Pre {
+    Some(self.inner.initial())
+  }
+  fn step_pre(&self, state: Self::Pre, char: X) -> Option<Self::Pre> {
+    let a = state.and_then(|x| self.inner.step_pre(x, char));
+    if a.is_some() || self.min == 0 {
+      Some(a)
+    } else {
+      None
+    }
+  }
+  fn step_active(&self, state: Self::Active, char: X) -> Option<Self::Active> {
+    let a = state.0.and_then(|x| self.inner.step_pre(x, char.clone()));
+    let x = state
+      .2
+      .into_iter()
+      .filter_map(|x| {
+        Some((
+          x.0,
+          x.1
+            .into_iter()
+            .map(|x| self.inner.step_post(x, char.clone()))
+            .collect::<Option<BTreeSet<_>>>()?,
+          self.inner.step_active(x.2, char.clone())?,
+        ))
+      })
+      .collect();
+    let x = self.threefa_closure(&a, x);
+    if a.is_some() || !x.is_empty() {
+      Some((a, false, x))
+    } else {
+      None
+    }
+  }
+  fn step_post(&self, state: Self::Post, char: X) -> Option<Self::Post> {
+    let x = state
+      .into_iter()
+      .filter_map(|x| {
+        x.into_iter()
+          .map(|x| self.inner.step_post(x, char.clone()))
+          .collect::<Option<BTreeSet<_>>>()
+      })
+      .collect::<BTreeSet<_>>();
+    if !x.is_empty() {
+      Some(x)
+    } else {
+      None
+    }
+  }
+  fn accept(&self, state: &Self::Post) -> bool {
+    state.iter().any(|x| x.iter().all(|x| self.inner.accept(x)))
+  }
+  fn decide(&self, state: &Self::Post) -> Option<bool> {
+    any_decided(state.iter().map(|x| {
+      let mut x = x.iter().map(|x| self.inner.decide(x));
+      if x.clone().any(|x| x == Some(false)) {
+        Some(false)
+      } else if x.all(|x| x == Some(true)) {
+        Some(true)
+      } else {
+        None
+      }
+    }))
+  }
+
+  fn enter(&self, state: Self::Pre) -> Option<Self::Active> {
+    let x = state
+      .clone()
+      .filter(|_| self.max != Some(0))
+      .and_then(|x| self.inner.enter(x))
+      .map(|x| (0, BTreeSet::new(), x))
+      .into_iter()
+      .collect();
+    let x = self.threefa_closure(&state, x);
+    if self.min == 0 || !x.is_empty() {
+      Some((state, true, x))
+    } else {
+      None
+    }
+  }
+  fn exit(&self, state: Self::Active) -> Option<Self::Post> {
+    let x = state
+      .2
+      .into_iter()
+      .filter(|x| x.0 + 1 >= self.min)
+      .filter_map(|mut x| {
+        x.1.insert(self.inner.exit(x.2)?);
+        Some(x.1)
+      })
+      .chain((state.1 && self.min == 0).then(BTreeSet::new))
+      .collect::<BTreeSet<_>>();
+    if !x.is_empty() {
+      Some(x)
+    } else {
+      None
+    }
+  }
+}
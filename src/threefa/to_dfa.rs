@@ -36,4 +36,12 @@ where
   fn accept(&self, state: &Self::State) -> bool {
     state.2.iter().any(|x| self.0.accept(x))
   }
+  // Undecided once anything can still enter or is active, since that may
+  // reach acceptance later.
+  fn decide(&self, state: &Self::State) -> Option<bool> {
+    match any_decided(state.2.iter().map(|x| self.0.decide(x))) {
+      Some(false) if state.0.is_some() || !state.1.is_empty() => None,
+      x => x,
+    }
+  }
 }
@@ -0,0 +1,72 @@
+use crate::*;
+use std::borrow::Borrow;
+
+// Pre and Active remember whether the previous byte was a word byte; Post
+// holds that until the next byte has been checked against it.
+impl<X: Borrow<u8>> ThreeFA<X> for WordBoundary {
+  type Pre = bool;
+  type Active = bool;
+  type Post = Option<bool>;
+  fn initial(&self) -> Self::Pre {
+    false
+  }
+  fn step_pre(&self, _: Self::Pre, char: X) -> Option<Self::Pre> {
+    Some(Class::WORD.contains(*char.borrow()))
+  }
+  fn step_active(&self, _: Self::Active, _: X) -> Option<Self::Active> {
+    None
+  }
+  fn step_post(&self, state: Self::Post, char: X) -> Option<Self::Post> {
+    match state {
+      Some(prev) if prev == Class::WORD.contains(*char.borrow()) => None,
+      _ => Some(None),
+    }
+  }
+  fn accept(&self, state: &Self::Post) -> bool {
+    state.unwrap_or(true)
+  }
+  fn decide(&self, state: &Self::Post) -> Option<bool> {
+    state.is_none().then_some(true)
+  }
+
+  fn enter(&self, state: Self::Pre) -> Option<Self::Active> {
+    Some(state)
+  }
+  fn exit(&self, state: Self::Active) -> Option<Self::Post> {
+    Some(Some(state))
+  }
+}
+
+impl<X: Borrow<u8>> ThreeFA<X> for NotWordBoundary {
+  type Pre = bool;
+  type Active = bool;
+  type Post = Option<bool>;
+  fn initial(&self) -> Self::Pre {
+    false
+  }
+  fn step_pre(&self, _: Self::Pre, char: X) -> Option<Self::Pre> {
+    Some(Class::WORD.contains(*char.borrow()))
+  }
+  fn step_active(&self, _: Self::Active, _: X) -> Option<Self::Active> {
+    None
+  }
+  fn step_post(&self, state: Self::Post, char: X) -> Option<Self::Post> {
+    match state {
+      Some(prev) if prev != Class::WORD.contains(*char.borrow()) => None,
+      _ => Some(None),
+    }
+  }
+  fn accept(&self, state: &Self::Post) -> bool {
+    !state.unwrap_or(false)
+  }
+  fn decide(&self, state: &Self::Post) -> Option<bool> {
+    state.is_none().then_some(true)
+  }
+
+  fn enter(&self, state: Self::Pre) -> Option<Self::Active> {
+    Some(state)
+  }
+  fn exit(&self, state: Self::Active) -> Option<Self::Post> {
+    Some(Some(state))
+  }
+}
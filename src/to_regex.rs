@@ -1,9 +1,87 @@
 use crate::*;
 
+type Paths<S> = BTreeMap<Option<S>, BTreeMap<Option<S>, String>>;
+
+static BYTES: [u8; 256] = {
+  let mut bytes = [0; 256];
+  let mut i = 0;
+  while i < 256 {
+    bytes[i] = i as u8;
+    i += 1;
+  }
+  bytes
+};
+
 pub fn to_regex<'a, D: Dfa<&'a u8>>(
   dfa: D,
   alphabet: impl Clone + IntoIterator<Item = &'a u8>,
 ) -> String
+where
+  D::State: Ord + Clone,
+{
+  to_regex_with(dfa, alphabet, |char| byte_label(*char))
+}
+
+pub fn to_regex_classes<'a, D: Dfa<&'a u8>>(dfa: D, classes: &ByteClasses) -> String
+where
+  D::State: Ord + Clone,
+{
+  let alphabet = classes
+    .representatives()
+    .into_iter()
+    .map(|x| &BYTES[x as usize])
+    .collect::<Vec<_>>();
+  to_regex_with(dfa, alphabet, |char| class_label(classes.members(*char)))
+}
+
+pub fn to_regex_bytes<'a, D: Dfa<&'a u8> + Classify>(dfa: D) -> String
+where
+  D::State: Ord + Clone,
+{
+  let classes = byte_classes(&dfa);
+  to_regex_classes(dfa, &classes)
+}
+
+pub(crate) fn byte_label(char: u8) -> String {
+  if (char as char).is_ascii() {
+    (char as char).to_string()
+  } else {
+    format!("\\{:02x}", char)
+  }
+}
+
+pub(crate) fn class_label(class: Class) -> String {
+  let bytes = (0..=u8::MAX).filter(|&x| class.contains(x));
+  if let [byte] = bytes.clone().collect::<Vec<_>>()[..] {
+    return byte_label(byte);
+  }
+  let (negated, class) = if bytes.count() > 128 {
+    ("^", class.negate())
+  } else {
+    ("", class)
+  };
+  let mut ranges = Vec::<(u8, u8)>::new();
+  for byte in (0..=u8::MAX).filter(|&x| class.contains(x)) {
+    match ranges.last_mut() {
+      Some((_, end)) if *end as usize + 1 == byte as usize => *end = byte,
+      _ => ranges.push((byte, byte)),
+    }
+  }
+  let ranges = ranges
+    .into_iter()
+    .map(|(from, to)| match from == to {
+      true => byte_label(from),
+      false => format!("{}-{}", byte_label(from), byte_label(to)),
+    })
+    .collect::<String>();
+  format!("[{negated}{ranges}]")
+}
+
+fn to_regex_with<'a, D: Dfa<&'a u8>>(
+  dfa: D,
+  alphabet: impl Clone + IntoIterator<Item = &'a u8>,
+  label: impl Copy + Fn(&u8) -> String,
+) -> String
 where
   D::State: Ord + Clone,
 {
@@ -14,7 +92,7 @@ where
   .into_iter()
   .collect();
 
-  visit(&dfa, alphabet, &mut paths, dfa.initial());
+  visit(&dfa, alphabet, label, &mut paths, dfa.initial());
 
   while let Some(state) = paths.keys().find_map(|x| x.clone()) {
     let key = Some(state);
@@ -49,7 +127,8 @@ where
   fn visit<'a, D: Dfa<&'a u8>>(
     dfa: &D,
     alphabet: impl Clone + IntoIterator<Item = &'a u8>,
-    paths: &mut BTreeMap<Option<D::State>, BTreeMap<Option<D::State>, String>>,
+    label: impl Copy + Fn(&u8) -> String,
+    paths: &mut Paths<D::State>,
     state: D::State,
   ) where
     D::State: Ord + Clone,
@@ -63,11 +142,7 @@ where
       for char in alphabet.clone() {
         if let Some(next) = dfa.next(state.clone(), char) {
           todo.insert(next.clone());
-          let str = if (*char as char).is_ascii() {
-            (*char as char).to_string()
-          } else {
-            format!("\\{:02x}", *char)
-          };
+          let str = label(char);
           map
             .entry(Some(next))
             .and_modify(|o| {
@@ -80,7 +155,7 @@ where
       map
     });
     for state in todo {
-      visit(dfa, alphabet.clone(), paths, state)
+      visit(dfa, alphabet.clone(), label, paths, state)
     }
   }
 }
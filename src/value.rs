@@ -0,0 +1,136 @@
+use crate::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Value {
+  Unit,
+  Bool(bool),
+  Usize(usize),
+  Option(Option<Box<Value>>),
+  Tuple(Vec<Value>),
+  Set(BTreeSet<Value>),
+}
+
+impl Finite for Value {}
+
+pub trait Dynamic: Sized {
+  fn into_value(self) -> Value;
+  fn from_value(value: Value) -> Self;
+}
+
+impl Dynamic for Value {
+  fn into_value(self) -> Value {
+    self
+  }
+  fn from_value(value: Value) -> Self {
+    value
+  }
+}
+
+impl Dynamic for () {
+  fn into_value(self) -> Value {
+    Value::Unit
+  }
+  fn from_value(value: Value) -> Self {
+    match value {
+      Value::Unit => (),
+      _ => unreachable!(),
+    }
+  }
+}
+
+impl Dynamic for bool {
+  fn into_value(self) -> Value {
+    Value::Bool(self)
+  }
+  fn from_value(value: Value) -> Self {
+    match value {
+      Value::Bool(x) => x,
+      _ => unreachable!(),
+    }
+  }
+}
+
+impl Dynamic for usize {
+  fn into_value(self) -> Value {
+    Value::Usize(self)
+  }
+  fn from_value(value: Value) -> Self {
+    match value {
+      Value::Usize(x) => x,
+      _ => unreachable!(),
+    }
+  }
+}
+
+impl<T: Dynamic> Dynamic for Option<T> {
+  fn into_value(self) -> Value {
+    Value::Option(self.map(|x| Box::new(x.into_value())))
+  }
+  fn from_value(value: Value) -> Self {
+    match value {
+      Value::Option(x) => x.map(|x| T::from_value(*x)),
+      _ => unreachable!(),
+    }
+  }
+}
+
+impl<T: Dynamic + Ord> Dynamic for BTreeSet<T> {
+  fn into_value(self) -> Value {
+    Value::Set(self.into_iter().map(T::into_value).collect())
+  }
+  fn from_value(value: Value) -> Self {
+    match value {
+      Value::Set(x) => x.into_iter().map(T::from_value).collect(),
+      _ => unreachable!(),
+    }
+  }
+}
+
+impl<T: Dynamic> Dynamic for (T,) {
+  fn into_value(self) -> Value {
+    Value::Tuple(vec![self.0.into_value()])
+  }
+  fn from_value(value: Value) -> Self {
+    match value {
+      Value::Tuple(x) => {
+        let [a] = <[Value; 1]>::try_from(x).unwrap();
+        (T::from_value(a),)
+      }
+      _ => unreachable!(),
+    }
+  }
+}
+
+impl<T: Dynamic, U: Dynamic> Dynamic for (T, U) {
+  fn into_value(self) -> Value {
+    Value::Tuple(vec![self.0.into_value(), self.1.into_value()])
+  }
+  fn from_value(value: Value) -> Self {
+    match value {
+      Value::Tuple(x) => {
+        let [a, b] = <[Value; 2]>::try_from(x).unwrap();
+        (T::from_value(a), U::from_value(b))
+      }
+      _ => unreachable!(),
+    }
+  }
+}
+
+impl<T: Dynamic, U: Dynamic, V: Dynamic> Dynamic for (T, U, V) {
+  fn into_value(self) -> Value {
+    Value::Tuple(vec![
+      self.0.into_value(),
+      self.1.into_value(),
+      self.2.into_value(),
+    ])
+  }
+  fn from_value(value: Value) -> Self {
+    match value {
+      Value::Tuple(x) => {
+        let [a, b, c] = <[Value; 3]>::try_from(x).unwrap();
+        (T::from_value(a), U::from_value(b), V::from_value(c))
+      }
+      _ => unreachable!(),
+    }
+  }
+}
@@ -0,0 +1,109 @@
+use crate::*;
+
+pub fn run<X, D: Dfa<X>>(dfa: D, str: impl IntoIterator<Item = X>) -> Option<D::State> {
+  let mut state = dfa.initial();
+  for char in str {
+    state = dfa.next(state, char)?;
+  }
+  Some(state)
+}
+
+pub fn viable_prefix<X: Clone, D: Dfa<X>>(
+  dfa: D,
+  prefix: impl IntoIterator<Item = X>,
+  alphabet: impl Clone + IntoIterator<Item = X>,
+) -> bool
+where
+  D::State: Clone + Ord,
+{
+  run(&dfa, prefix).is_some_and(|x| is_empty_from(&dfa, x, alphabet).is_err())
+}
+
+pub fn allowed_next<X: Clone, D: Dfa<X>>(
+  dfa: D,
+  state: D::State,
+  alphabet: impl Clone + IntoIterator<Item = X>,
+) -> Vec<X>
+where
+  D::State: Clone + Ord,
+{
+  alphabet
+    .clone()
+    .into_iter()
+    .filter(|char| {
+      dfa
+        .next(state.clone(), char.clone())
+        .is_some_and(|x| is_empty_from(&dfa, x, alphabet.clone()).is_err())
+    })
+    .collect()
+}
+
+// Yields every accepted continuation from `state`, shortest first and in
+// alphabet order within a length. The states reachable from `state` are
+// explored once; `ready[r]` holds the states that accept after exactly `r`
+// more symbols, so the search for each length only follows edges that lead
+// to a completion, and the search stops once no live state is `length`
+// symbols away.
+pub fn completions<X: Clone, D: Dfa<X>>(
+  dfa: D,
+  state: D::State,
+  alphabet: impl Clone + IntoIterator<Item = X>,
+) -> impl Iterator<Item = Vec<X>>
+where
+  D::State: Clone + Ord,
+{
+  let symbols = alphabet.clone().into_iter().collect::<Vec<_>>();
+  let (states, edges) = explore(&dfa, state, alphabet);
+  let live = live(&dfa, &states, &edges);
+  let mut ready = vec![states.iter().map(|x| dfa.accept(x)).collect::<Vec<_>>()];
+  let mut frontier = BTreeSet::from_iter(live[0].then_some(0));
+  let mut length = 0;
+  let mut stack = vec![];
+  let mut path = vec![];
+  if ready[0][0] {
+    stack.push((0, 0));
+  }
+  std::iter::from_fn(move || loop {
+    let Some((state, char)) = stack.last_mut() else {
+      frontier = frontier
+        .iter()
+        .flat_map(|&x| edges[x].iter().flatten().copied())
+        .filter(|&x| live[x])
+        .collect();
+      if frontier.is_empty() {
+        return None;
+      }
+      length += 1;
+      let next = edges
+        .iter()
+        .map(|x| x.iter().flatten().any(|&x| ready[length - 1][x]))
+        .collect();
+      ready.push(next);
+      if ready[length][0] {
+        stack.push((0, 0));
+      }
+      continue;
+    };
+    let depth = path.len();
+    if depth == length {
+      stack.pop();
+      let found = path.clone();
+      path.pop();
+      return Some(found);
+    }
+    let remaining = &ready[length - depth - 1];
+    let found = (*char..symbols.len()).find(|&x| edges[*state][x].is_some_and(|x| remaining[x]));
+    match found {
+      Some(x) => {
+        let next = edges[*state][x].unwrap();
+        *char = x + 1;
+        path.push(symbols[x].clone());
+        stack.push((next, 0));
+      }
+      None => {
+        stack.pop();
+        path.pop();
+      }
+    }
+  })
+}